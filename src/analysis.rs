@@ -0,0 +1,379 @@
+//! A module for image feature analysis
+
+use std::collections::HashSet;
+
+use crate::{colorspace, error};
+use crate::image::{BaseImage, Image};
+use crate::error::{ImgProcResult, ImgProcError};
+
+/// Computes the local binary pattern (LBP) of each pixel of `input` after converting it to
+/// grayscale. Each output pixel is an 8-bit code, where bit `i` is set if the `i`th neighbor is
+/// greater than or equal to the center pixel. Neighbors are numbered clockwise starting from the
+/// upper left corner, and pixels outside the image are treated as copies of the nearest edge pixel
+pub fn local_binary_pattern(input: &Image<u8>) -> ImgProcResult<Image<u8>> {
+    error::check_non_empty(input)?;
+
+    let gray = to_grayscale(input);
+    let (width, height) = gray.info().wh();
+
+    const NEIGHBORS: [(i32, i32); 8] = [(-1, -1), (0, -1), (1, -1), (1, 0),
+                                        (1, 1), (0, 1), (-1, 1), (-1, 0)];
+
+    let mut data = Vec::with_capacity((width * height) as usize);
+    for y in 0..height {
+        for x in 0..width {
+            let center = gray.get_pixel_unchecked(x, y)[0];
+            let mut code = 0u8;
+
+            for (i, (dx, dy)) in NEIGHBORS.iter().enumerate() {
+                let x_clamp = (x as i32 + dx).clamp(0, width as i32 - 1) as u32;
+                let y_clamp = (y as i32 + dy).clamp(0, height as i32 - 1) as u32;
+
+                if gray.get_pixel_unchecked(x_clamp, y_clamp)[0] >= center {
+                    code |= 1 << i;
+                }
+            }
+
+            data.push(code);
+        }
+    }
+
+    Ok(Image::from_vec(width, height, 1, false, data))
+}
+
+/// Computes a 64-bit perceptual hash (pHash) of `input`. The image is converted to grayscale and
+/// shrunk to 32x32, and the lowest 8x8 frequencies of its discrete cosine transform are compared
+/// against their median: bit `8 * v + u` is set if frequency `(u, v)` is greater than the median.
+/// Similar images have hashes with a small [`hamming_distance`](fn.hamming_distance.html).
+/// Returns 0 for an empty image
+pub fn phash(input: &Image<u8>) -> u64 {
+    const SIZE: usize = 32;
+
+    let small = match grayscale_thumbnail(input, SIZE as u32, SIZE as u32) {
+        Some(small) => small,
+        None => return 0,
+    };
+
+    let cos: Vec<f64> = (0..(8 * SIZE))
+        .map(|i| {
+            let (u, x) = (i / SIZE, i % SIZE);
+            (((2 * x + 1) * u) as f64 * std::f64::consts::PI / (2 * SIZE) as f64).cos()
+        })
+        .collect();
+
+    let mut dct = Vec::with_capacity(64);
+    for v in 0..8 {
+        for u in 0..8 {
+            let mut sum = 0.0;
+            for y in 0..SIZE {
+                for x in 0..SIZE {
+                    sum += small.data()[y * SIZE + x] * cos[u * SIZE + x] * cos[v * SIZE + y];
+                }
+            }
+            dct.push(sum);
+        }
+    }
+
+    let mut sorted = dct.clone();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let median = (sorted[31] + sorted[32]) / 2.0;
+
+    threshold_bits(&dct, median)
+}
+
+/// Returns the number of bits that differ between the hashes `a` and `b`
+pub fn hamming_distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+/// Computes a 64-bit average hash (aHash) of `input`. The image is converted to grayscale and
+/// shrunk to 8x8, and bit `8 * y + x` is set if pixel `(x, y)` is brighter than the mean. Returns
+/// 0 for an empty image
+pub fn ahash(input: &Image<u8>) -> u64 {
+    match grayscale_thumbnail(input, 8, 8) {
+        Some(small) => {
+            let mean = small.data().iter().sum::<f64>() / 64.0;
+            threshold_bits(small.data(), mean)
+        },
+        None => 0,
+    }
+}
+
+/// Computes a 64-bit difference hash (dHash) of `input`. The image is converted to grayscale and
+/// shrunk to 9x8, and bit `8 * y + x` is set if pixel `(x, y)` is brighter than pixel
+/// `(x + 1, y)`. Returns 0 for an empty image
+pub fn dhash(input: &Image<u8>) -> u64 {
+    match grayscale_thumbnail(input, 9, 8) {
+        Some(small) => {
+            let diffs: Vec<f64> = small.data()
+                .chunks(9)
+                .flat_map(|row| row.windows(2).map(|pair| pair[0] - pair[1]))
+                .collect();
+            threshold_bits(&diffs, 0.0)
+        },
+        None => 0,
+    }
+}
+
+/// Returns the number of distinct colors in `input`, where a color is the full tuple of channel
+/// values of a pixel (including alpha)
+pub fn count_colors(input: &Image<u8>) -> usize {
+    color_set(input).len()
+}
+
+/// Returns the distinct colors in `input` in ascending order, where a color is the full tuple of
+/// channel values of a pixel (including alpha)
+pub fn unique_colors(input: &Image<u8>) -> Vec<Vec<u8>> {
+    let mut colors: Vec<Vec<u8>> = color_set(input)
+        .into_iter()
+        .map(|color| color.to_vec())
+        .collect();
+    colors.sort_unstable();
+
+    colors
+}
+
+/// Returns the coordinates and channel index `(x, y, c)` of the first sample at which `a` and `b`
+/// differ, scanning row by row from the upper left corner, or `None` if the images are equal.
+/// Returns an error if `a` and `b` have different dimensions or numbers of channels
+pub fn first_difference(a: &Image<u8>, b: &Image<u8>) -> ImgProcResult<Option<(u32, u32, usize)>> {
+    error::check_dimensions_equal(a.info().wh(), b.info().wh())?;
+    error::check_equal(a.info().channels, b.info().channels, "channels")?;
+
+    let channels = a.info().channels as usize;
+    let width = a.info().width as usize;
+
+    Ok(a.data().iter()
+        .zip(b.data().iter())
+        .position(|(val_a, val_b)| val_a != val_b)
+        .map(|i| {
+            let pixel = i / channels;
+            ((pixel % width) as u32, (pixel / width) as u32, i % channels)
+        }))
+}
+
+/// The mean and (population) variance of each channel of an image
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelStats {
+    /// The mean of each channel
+    pub mean: Vec<f64>,
+
+    /// The variance of each channel
+    pub variance: Vec<f64>,
+}
+
+/// Computes the mean and variance of each channel of `input`, including alpha. See
+/// [`RunningStats`](struct.RunningStats.html) to compute the same statistics without holding the
+/// whole image in memory
+pub fn channel_stats(input: &Image<u8>) -> ChannelStats {
+    let channels = input.info().channels as usize;
+    let size = input.info().size().max(1) as f64;
+
+    let mut mean = vec![0.0; channels];
+    for p in input.data().chunks_exact(channels) {
+        for (m, val) in mean.iter_mut().zip(p.iter()) {
+            *m += *val as f64 / size;
+        }
+    }
+
+    let mut variance = vec![0.0; channels];
+    for p in input.data().chunks_exact(channels) {
+        for ((v, m), val) in variance.iter_mut().zip(mean.iter()).zip(p.iter()) {
+            *v += (*val as f64 - m) * (*val as f64 - m) / size;
+        }
+    }
+
+    ChannelStats { mean, variance }
+}
+
+/// An accumulator that computes the mean and variance of each channel of an image from rows of
+/// pixels fed to it one at a time, using Welford's algorithm. This allows statistics to be
+/// computed for images that are too large to hold in memory
+#[derive(Debug, Clone, Default)]
+pub struct RunningStats {
+    count: u64,
+    mean: Vec<f64>,
+    m2: Vec<f64>, // The sum of squared differences from the current mean
+}
+
+impl RunningStats {
+    /// Creates a new `RunningStats` that has not been fed any pixels
+    pub fn new() -> Self {
+        RunningStats::default()
+    }
+
+    /// Adds the pixels in `row`, which holds interleaved pixels with `channels` channels each
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of `row` is not a multiple of `channels`, or if `channels` differs
+    /// from the number of channels previously fed
+    pub fn update(&mut self, row: &[u8], channels: u8) {
+        let channels = channels as usize;
+        assert!(channels > 0 && row.len() % channels == 0,
+                "row length {} is not a multiple of {} channels", row.len(), channels);
+
+        if self.mean.is_empty() {
+            self.mean = vec![0.0; channels];
+            self.m2 = vec![0.0; channels];
+        }
+        assert_eq!(self.mean.len(), channels, "number of channels changed");
+
+        for p in row.chunks_exact(channels) {
+            self.count += 1;
+            for (c, val) in p.iter().enumerate() {
+                let val = *val as f64;
+                let delta = val - self.mean[c];
+                self.mean[c] += delta / self.count as f64;
+                self.m2[c] += delta * (val - self.mean[c]);
+            }
+        }
+    }
+
+    /// Returns the statistics of all the pixels fed so far. The variance of a channel is 0 if no
+    /// pixels have been fed
+    pub fn finish(&self) -> ChannelStats {
+        ChannelStats {
+            mean: self.mean.clone(),
+            variance: self.m2.iter().map(|m2| m2 / self.count.max(1) as f64).collect(),
+        }
+    }
+}
+
+// Returns the set of distinct pixels in `input`
+fn color_set(input: &Image<u8>) -> HashSet<&[u8]> {
+    input.data()
+        .chunks_exact(input.info().channels as usize)
+        .collect()
+}
+
+// Converts `input` to grayscale and shrinks it to `width x height` by averaging the pixels that
+// fall in each output pixel, or returns `None` if `input` is empty. Output pixels that no input
+// pixel falls in (when enlarging) take the value of the nearest input pixel
+fn grayscale_thumbnail(input: &Image<u8>, width: u32, height: u32) -> Option<Image<f64>> {
+    if error::check_non_empty(input).is_err() {
+        return None;
+    }
+
+    let gray = to_grayscale(input);
+    let (width_in, height_in) = gray.info().wh();
+    let mut sums = vec![0.0; (width * height) as usize];
+    let mut counts = vec![0u32; (width * height) as usize];
+
+    for y in 0..height_in {
+        for x in 0..width_in {
+            let i = ((y as u64 * height as u64 / height_in as u64) * width as u64
+                + x as u64 * width as u64 / width_in as u64) as usize;
+            sums[i] += gray.get_pixel_unchecked(x, y)[0] as f64;
+            counts[i] += 1;
+        }
+    }
+
+    let data = (0..(width * height))
+        .map(|i| {
+            let i_usize = i as usize;
+            if counts[i_usize] > 0 {
+                return sums[i_usize] / counts[i_usize] as f64;
+            }
+
+            let x = ((i % width) as f64 + 0.5) * width_in as f64 / width as f64;
+            let y = ((i / width) as f64 + 0.5) * height_in as f64 / height as f64;
+            gray.get_pixel_unchecked(x as u32, y as u32)[0] as f64
+        })
+        .collect();
+
+    Some(Image::from_vec(width, height, 1, false, data))
+}
+
+// Packs up to 64 comparisons of `vals` against `threshold` into a hash, with bit `i` set if
+// `vals[i] > threshold`
+fn threshold_bits(vals: &[f64], threshold: f64) -> u64 {
+    vals.iter()
+        .enumerate()
+        .filter(|(_, val)| **val > threshold)
+        .fold(0, |hash, (i, _)| hash | (1 << i))
+}
+
+// Converts `input` to a single-channel grayscale image, dropping any alpha channel
+pub(crate) fn to_grayscale(input: &Image<u8>) -> Image<u8> {
+    let gray = if input.info().channels_non_alpha() == 1 {
+        input.clone()
+    } else {
+        colorspace::rgb_to_grayscale(input)
+    };
+
+    let (width, height, channels) = gray.info().whc();
+    let data = gray.data().iter().step_by(channels as usize).copied().collect();
+
+    Image::from_vec(width, height, 1, false, data)
+}
+
+/// Computes the histogram of oriented gradients (HOG) descriptor of `input` after converting it
+/// to grayscale. Unsigned gradient orientations (0 to 180 degrees) are accumulated into `bins`
+/// bins, with bin `i` centered at `i * 180 / bins` degrees, for each `cell_size x cell_size`
+/// cell, weighted by gradient magnitude. Cell histograms are then L2-normalized over overlapping
+/// 2x2 blocks of cells, and the normalized blocks are concatenated in row-major order. Pixels in
+/// a partial cell at the right or bottom edge are ignored
+pub fn hog(input: &Image<u8>, cell_size: u32, bins: u32) -> ImgProcResult<Vec<f32>> {
+    error::check_non_empty(input)?;
+
+    error::check_positive(cell_size, "cell_size")?;
+    error::check_positive(bins, "bins")?;
+
+    let gray = to_grayscale(input);
+    let (width, height) = gray.info().wh();
+    let (cells_x, cells_y) = (width / cell_size, height / cell_size);
+
+    if cells_x < 2 || cells_y < 2 {
+        return Err(ImgProcError::InvalidArgError(format!("invalid cell_size: image must contain \
+            at least 2x2 cells of size {}", cell_size)));
+    }
+
+    let bins = bins as usize;
+    let bin_width = 180.0 / bins as f32;
+    let mut hist = vec![0.0f32; (cells_x * cells_y) as usize * bins];
+
+    for y in 0..(cells_y * cell_size) {
+        for x in 0..(cells_x * cell_size) {
+            let val = |x: i32, y: i32| {
+                let x = x.clamp(0, width as i32 - 1) as u32;
+                let y = y.clamp(0, height as i32 - 1) as u32;
+                gray.get_pixel_unchecked(x, y)[0] as f32
+            };
+
+            let gx = val(x as i32 + 1, y as i32) - val(x as i32 - 1, y as i32);
+            let gy = val(x as i32, y as i32 + 1) - val(x as i32, y as i32 - 1);
+            let mag = (gx * gx + gy * gy).sqrt();
+            if mag == 0.0 {
+                continue;
+            }
+
+            // Bin i is centered at i * bin_width degrees. Split each vote between the two nearest bins
+            let pos = gy.atan2(gx).to_degrees().rem_euclid(180.0) / bin_width;
+            let frac = pos - pos.floor();
+            let bin_0 = (pos.floor() as i32).rem_euclid(bins as i32) as usize;
+            let bin_1 = (bin_0 + 1) % bins;
+
+            let cell = ((y / cell_size) * cells_x + x / cell_size) as usize * bins;
+            hist[cell + bin_0] += mag * (1.0 - frac);
+            hist[cell + bin_1] += mag * frac;
+        }
+    }
+
+    let mut output = Vec::with_capacity(((cells_x - 1) * (cells_y - 1)) as usize * 4 * bins);
+    for y in 0..(cells_y - 1) {
+        for x in 0..(cells_x - 1) {
+            let start = output.len();
+
+            for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)].iter() {
+                let cell = ((y + dy) * cells_x + x + dx) as usize * bins;
+                output.extend_from_slice(&hist[cell..(cell + bins)]);
+            }
+
+            let norm = (output[start..].iter().map(|val| val * val).sum::<f32>() + 1e-6).sqrt();
+            output[start..].iter_mut().for_each(|val| *val /= norm);
+        }
+    }
+
+    Ok(output)
+}
@@ -1,10 +1,15 @@
 //! A module for image colorspace conversion operations
+//!
+//! Conversions only operate on the color channels of an image. If the image has an alpha channel,
+//! it is passed through unchanged (rescaled only where the output range differs, as in
+//! [`rgb_to_hsv`](fn.rgb_to_hsv.html))
 
 use std::cmp;
 
+use crate::{error, util};
 use crate::enums::White;
-use crate::image::Image;
-use crate::util;
+use crate::error::ImgProcResult;
+use crate::image::{BaseImage, Image};
 use crate::util::constants::{GAMMA, SRGB_TO_XYZ_MAT, XYZ_TO_SRGB_MAT};
 
 /// Converts an image from RGB to Grayscale
@@ -63,13 +68,39 @@ pub fn unlinearize_srgb(input: &Image<f64>) -> Image<u8> {
     }, |a| a.round() as u8)
 }
 
-/// Converts an image from linearized sRGB to CIE XYZ
+/// Decodes an sRGB image into linear light using the standard piecewise sRGB transfer function
+///
+/// * Input: sRGB image with channels in range [0, 255]
+/// * Output: linear light image with channels in range [0, 1]
+pub fn srgb_to_linear(input: &Image<u8>) -> Image<f64> {
+    let mut lookup_table: [f64; 256] = [0.0; 256];
+    util::generate_lookup_table(&mut lookup_table, |i| util::srgb_decode_fn(i as f64 / 255.0));
+
+    input.map_channels_if_alpha(|i| lookup_table[i as usize], |a| a as f64)
+}
+
+/// Encodes a linear light image into sRGB using the standard piecewise sRGB transfer function
+///
+/// * Input: linear light image with channels in range [0, 1]
+/// * Output: sRGB image with channels in range [0, 255]
+pub fn linear_to_srgb(input: &Image<f64>) -> Image<u8> {
+    input.map_channels_if_alpha(|num| {
+        (util::srgb_encode_fn(num.clamp(0.0, 1.0)) * 255.0).round() as u8
+    }, |a| a.round() as u8)
+}
+
+/// Converts an image from linearized sRGB to CIE XYZ. A single-channel (grayscale) image is
+/// treated as having equal R, G, and B channels
 ///
 /// * Input: linearized sRGB image with channels in range [0, 1]
 /// * Output: CIE XYZ image with channels in range [0, 1]
 pub fn srgb_lin_to_xyz(input: &Image<f64>) -> Image<f64> {
     input.map_pixels_if_alpha(|channels, p_out| {
-        util::vector_mul_mut(&SRGB_TO_XYZ_MAT, channels, p_out).unwrap()
+        if channels.len() == 1 {
+            util::vector_mul_mut(&SRGB_TO_XYZ_MAT, &[channels[0]; 3], p_out).unwrap()
+        } else {
+            util::vector_mul_mut(&SRGB_TO_XYZ_MAT, channels, p_out).unwrap()
+        }
     }, |a| a)
 }
 
@@ -83,6 +114,38 @@ pub fn xyz_to_srgb_lin(input: &Image<f64>) -> Image<f64> {
     }, |a| a)
 }
 
+/// Brings out-of-gamut linearized sRGB values into range by desaturating each pixel towards its
+/// luminance rather than clamping each channel independently, which reduces hue shifts
+///
+/// * Input: linearized sRGB image with channels possibly outside of range [0, 1]
+/// * Output: linearized sRGB image with channels in range [0, 1]
+pub fn clamp_to_gamut(input: &Image<f64>) -> Image<f64> {
+    input.map_pixels_if_alpha(|channels, p_out| {
+        // Use the Y row of the sRGB to CIE XYZ matrix as the luminance weights
+        let lum = if channels.len() == 3 {
+            SRGB_TO_XYZ_MAT[3] * channels[0]
+                + SRGB_TO_XYZ_MAT[4] * channels[1]
+                + SRGB_TO_XYZ_MAT[5] * channels[2]
+        } else {
+            channels.iter().sum::<f64>() / channels.len() as f64
+        }.clamp(0.0, 1.0);
+
+        // Find the largest fraction of the original saturation that stays in gamut
+        let mut t: f64 = 1.0;
+        for channel in channels.iter() {
+            if *channel > 1.0 {
+                t = t.min((1.0 - lum) / (channel - lum));
+            } else if *channel < 0.0 {
+                t = t.min(lum / (lum - channel));
+            }
+        }
+
+        for channel in channels.iter() {
+            p_out.push((lum + t * (channel - lum)).clamp(0.0, 1.0));
+        }
+    }, |a| a)
+}
+
 /// Converts an image from CIE XYZ to CIELAB
 ///
 /// * Input: CIE XYZ image with channels in range [0, 1]
@@ -117,12 +180,14 @@ pub fn lab_to_xyz(input: &Image<f64>, ref_white: &White) -> Image<f64> {
     }, |a| a)
 }
 
-/// Converts an image from RGB to HSV
+/// Converts an image from RGB to HSV. A single-channel (grayscale) image is treated as having
+/// equal R, G, and B channels
 ///
 /// * Input: RGB image with channels in range [0, 255]
 /// * Output: HSV image with channels in range [0, 1]
 pub fn rgb_to_hsv(input: &Image<u8>) -> Image<f64> {
     input.map_pixels_if_alpha(|channels, p_out| {
+        let channels = if channels.len() == 1 { [channels[0]; 3] } else { [channels[0], channels[1], channels[2]] };
         let max: u8 = cmp::max(cmp::max(channels[0], channels[1]), channels[2]);
         let min: u8 = cmp::min(cmp::min(channels[0], channels[1]), channels[2]);
         let range = (max - min) as f64 / 255.0;
@@ -187,7 +252,8 @@ pub fn hsv_to_rgb(input: &Image<f64>) -> Image<u8> {
     }, |a| (a * 255.0).round() as u8)
 }
 
-/// Converts an image from sRGB to CIE XYZ
+/// Converts an image from sRGB to CIE XYZ. A single-channel (grayscale) image is treated as
+/// having equal R, G, and B channels
 ///
 /// * Input: sRGB image with channels in range [0, 255]
 /// * Output: CIE XYZ image with channels in range [0, 1]
@@ -205,7 +271,8 @@ pub fn xyz_to_srgb(input: &Image<f64>) -> Image<u8> {
     unlinearize_srgb(&srgb)
 }
 
-/// Converts an image from sRGB to CIELAB
+/// Converts an image from sRGB to CIELAB. A single-channel (grayscale) image is treated as
+/// having equal R, G, and B channels
 ///
 /// * Input: sRGB image with channels in range [0, 255]
 /// * Output: CIELAB image with L* channel range [0, 100] and a*, b* channels range [-128, 127]
@@ -222,3 +289,134 @@ pub fn lab_to_srgb(input: &Image<f64>, ref_white: &White) -> Image<u8> {
     let xyz = lab_to_xyz(input, ref_white);
     xyz_to_srgb(&xyz)
 }
+
+/// Swaps channels `a` and `b` of every pixel
+pub fn swap_channels(input: &Image<u8>, a: usize, b: usize) -> ImgProcResult<Image<u8>> {
+    let channels = input.info().channels as usize;
+    error::check_in_range(a, 0, channels - 1, "a")?;
+    error::check_in_range(b, 0, channels - 1, "b")?;
+
+    let mut output = input.clone();
+    for pixel in output.data_mut().chunks_mut(channels) {
+        pixel.swap(a, b);
+    }
+
+    Ok(output)
+}
+
+/// Converts an image from BGR to RGB (or from RGB to BGR) by swapping the first and third channels
+pub fn bgr_to_rgb(input: &Image<u8>) -> ImgProcResult<Image<u8>> {
+    swap_channels(input, 0, 2)
+}
+
+/// Extracts channel `channel` of every pixel as a single-channel image
+pub fn extract_channel(input: &Image<u8>, channel: usize) -> ImgProcResult<Image<u8>> {
+    error::check_in_range(channel, 0, input.info().channels as usize - 1, "channel")?;
+
+    let (width, height, channels) = input.info().whc();
+    let data = input.data()
+        .iter()
+        .skip(channel)
+        .step_by(channels as usize)
+        .copied()
+        .collect();
+
+    Ok(Image::from_vec(width, height, 1, false, data))
+}
+
+/// Applies `f` to the channels of every pixel whose indices are listed in `channels`, leaving the
+/// other channels unchanged
+pub fn map_selected_channels<F>(input: &Image<u8>, channels: &[usize], f: F) -> ImgProcResult<Image<u8>>
+    where F: Fn(u8) -> u8 {
+    let num_channels = input.info().channels as usize;
+    let mut selected = vec![false; num_channels];
+    for channel in channels.iter() {
+        error::check_in_range(*channel, 0, num_channels - 1, "channel")?;
+        selected[*channel] = true;
+    }
+
+    let mut output = input.clone();
+    for pixel in output.data_mut().chunks_mut(num_channels) {
+        for (val, is_selected) in pixel.iter_mut().zip(selected.iter()) {
+            if *is_selected {
+                *val = f(*val);
+            }
+        }
+    }
+
+    Ok(output)
+}
+
+/// Multiplies each color channel by the alpha channel (scaled to the range [0, 1])
+pub fn premultiply_alpha(input: &Image<u8>) -> ImgProcResult<Image<u8>> {
+    error::check_alpha(input)?;
+
+    Ok(input.map_pixels(|channels, p_out| {
+        let (colors, alpha) = channels.split_at(channels.len() - 1);
+
+        for channel in colors.iter() {
+            p_out.push(((*channel as f64) * (alpha[0] as f64) / 255.0).round() as u8);
+        }
+        p_out.push(alpha[0]);
+    }))
+}
+
+/// Divides each color channel by the alpha channel (scaled to the range [0, 1]), reversing
+/// [`premultiply_alpha`](fn.premultiply_alpha.html). Color channels of fully transparent pixels
+/// are set to 0
+pub fn unpremultiply_alpha(input: &Image<u8>) -> ImgProcResult<Image<u8>> {
+    error::check_alpha(input)?;
+
+    Ok(input.map_pixels(|channels, p_out| {
+        let (colors, alpha) = channels.split_at(channels.len() - 1);
+
+        for channel in colors.iter() {
+            if alpha[0] == 0 {
+                p_out.push(0);
+            } else {
+                p_out.push(((*channel as f64) * 255.0 / (alpha[0] as f64)).round().min(255.0) as u8);
+            }
+        }
+        p_out.push(alpha[0]);
+    }))
+}
+
+/// Computes the CIE76 color difference (Delta E) between each pair of corresponding pixels in two
+/// sRGB images, using the D65 reference white. Alpha channels are ignored
+///
+/// * Input: sRGB images with channels in range [0, 255]
+/// * Output: single-channel image of Delta E values
+pub fn delta_e_cie76(a: &Image<u8>, b: &Image<u8>) -> ImgProcResult<Image<f32>> {
+    delta_e(a, b, |lab_a, lab_b| {
+        let mut sum = 0.0;
+        for (c_a, c_b) in lab_a.iter().zip(lab_b.iter()) {
+            sum += (c_a - c_b) * (c_a - c_b);
+        }
+
+        sum.sqrt()
+    })
+}
+
+/// Computes the CIEDE2000 color difference (Delta E) between each pair of corresponding pixels in
+/// two sRGB images, using the D65 reference white. Alpha channels are ignored
+///
+/// * Input: sRGB images with channels in range [0, 255]
+/// * Output: single-channel image of Delta E values
+pub fn delta_e_ciede2000(a: &Image<u8>, b: &Image<u8>) -> ImgProcResult<Image<f32>> {
+    delta_e(a, b, util::ciede2000_fn)
+}
+
+// Applies the color difference formula `f` to each pair of corresponding pixels in `a` and `b`
+// after converting them to CIELAB
+fn delta_e<F>(a: &Image<u8>, b: &Image<u8>, f: F) -> ImgProcResult<Image<f32>>
+    where F: Fn(&[f64], &[f64]) -> f64 {
+    error::check_equal(a.info().whca(), b.info().whca(), "image dimensions")?;
+    error::check_rgb(a)?;
+
+    let lab_a = srgb_to_lab(a, &White::D65);
+    let lab_b = srgb_to_lab(b, &White::D65);
+
+    lab_a.zip_map(&lab_b, |p_a, p_b, p_out| {
+        p_out.push(f(&p_a[..3], &p_b[..3]) as f32);
+    })
+}
@@ -1,5 +1,6 @@
 //! A module for image channel type conversions
 
+use crate::util;
 use crate::image::Image;
 use crate::error::ImgProcResult;
 
@@ -10,14 +11,36 @@ pub fn scale_channels(input: &Image<f64>, current_min: f64, scaled_min: f64, cur
     }))
 }
 
+/// Clamps every channel, including alpha channels, to the range `min` to `max`
+pub fn clamp_channels(input: &Image<f64>, min: f64, max: f64) -> Image<f64> {
+    input.map_channels(|channel| channel.clamp(min, max))
+}
+
 /// Converts an `Image<f64>` with channels in range 0 to `scale` to an `Image<u8>` with channels
-/// in range 0 to 255
+/// in range 0 to 255. Channels outside of the range 0 to `scale` (including infinities) are
+/// clamped, and NaN channels become 0
 pub fn f64_to_u8_scale(input: &Image<f64>, scale: u32) -> Image<u8> {
-    input.map_channels(|channel| (channel / scale as f64 * 255.0).round() as u8)
+    input.map_channels(|channel| util::round_to_u8(channel / scale as f64 * 255.0))
 }
 
 /// Converts an `Image<u8>` to with channels in range 0 to 255 to an `Image<f64>` with channels
 /// in range 0 to `scale`
 pub fn u8_to_f64_scale(input: &Image<u8>, scale: u32) -> Image<f64> {
     input.map_channels(|channel| ((channel as f64 / 255.0) * scale as f64))
-}
\ No newline at end of file
+}
+
+/// Converts an `Image<u16>` with channels in range 0 to 65535 to an `Image<f64>` with channels
+/// in range 0 to `scale`
+pub fn u16_to_f64_scale(input: &Image<u16>, scale: u32) -> Image<f64> {
+    input.map_channels(|channel| channel as f64 / 65535.0 * scale as f64)
+}
+
+/// Converts an `Image<f64>` with channels in range 0 to `scale` to an `Image<u16>` with channels
+/// in range 0 to 65535. Channels outside of the range 0 to `scale` (including infinities) are
+/// clamped, and NaN channels become 0
+pub fn f64_to_u16_scale(input: &Image<f64>, scale: u32) -> Image<u16> {
+    input.map_channels(|channel| {
+        let val = channel / scale as f64 * 65535.0;
+        if val.is_nan() { 0 } else { val.round().clamp(0.0, 65535.0) as u16 }
+    })
+}
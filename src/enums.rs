@@ -46,6 +46,86 @@ pub enum Scale {
 
     /// Lanczos resampling with kernel size of 3
     Lanczos,
+
+    /// Triangle (linear tent) filter. When downscaling, the tent is widened by the scale factor so
+    /// that it averages every input pixel it covers
+    Triangle,
+
+    /// Catmull-Rom cubic interpolation, which passes through the original sample points
+    CatmullRom,
+}
+
+/// An enum for convolution output modes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConvMode {
+    /// Output has the same dimensions as the input, with edge pixels repeated indefinitely
+    Same,
+
+    /// Output only contains pixels where the kernel fully overlaps the input, so each dimension
+    /// shrinks by the kernel size minus one
+    Valid,
+}
+
+/// An enum for handling pixels beyond the edges of an image
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Padding {
+    /// Edge pixels are repeated indefinitely
+    Clamp,
+
+    /// Pixels are mirrored about the edge pixel, which is not repeated
+    Reflect,
+}
+
+/// An enum for the position of an image relative to a larger or smaller canvas
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Anchor {
+    /// Aligned with the top and left edges
+    TopLeft,
+
+    /// Aligned with the top edge and centered horizontally
+    Top,
+
+    /// Aligned with the top and right edges
+    TopRight,
+
+    /// Aligned with the left edge and centered vertically
+    Left,
+
+    /// Centered horizontally and vertically
+    Center,
+
+    /// Aligned with the right edge and centered vertically
+    Right,
+
+    /// Aligned with the bottom and left edges
+    BottomLeft,
+
+    /// Aligned with the bottom edge and centered horizontally
+    Bottom,
+
+    /// Aligned with the bottom and right edges
+    BottomRight,
+}
+
+/// An enum for frequency domain filters. Frequencies are measured in cycles per pixel
+pub enum FrequencyFilter {
+    /// Keeps frequencies below the cutoff frequency
+    Lowpass(f64),
+
+    /// Keeps frequencies above the cutoff frequency
+    Highpass(f64),
+
+    /// Keeps frequencies between the low and high cutoff frequencies
+    Bandpass(f64, f64),
+}
+
+/// An enum for the rolloff of frequency domain filters
+pub enum Rolloff {
+    /// Frequencies are either kept or removed entirely
+    Ideal,
+
+    /// Butterworth rolloff of the given order
+    Butterworth(u32),
 }
 
 /// An enum for image reflection axes
@@ -57,6 +137,35 @@ pub enum Refl {
     Horizontal,
 }
 
+/// An enum for the 8 EXIF orientation values, each describing the transformation that must be
+/// applied to the stored image to display it upright
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Orientation {
+    /// EXIF value 1: the image is already upright
+    Normal,
+
+    /// EXIF value 2: the image must be mirrored left to right
+    FlipHorizontal,
+
+    /// EXIF value 3: the image must be rotated 180 degrees
+    Rotate180,
+
+    /// EXIF value 4: the image must be mirrored top to bottom
+    FlipVertical,
+
+    /// EXIF value 5: the image must be transposed (mirrored across the main diagonal)
+    Transpose,
+
+    /// EXIF value 6: the image must be rotated 90 degrees clockwise
+    Rotate90,
+
+    /// EXIF value 7: the image must be transversed (mirrored across the anti-diagonal)
+    Transverse,
+
+    /// EXIF value 8: the image must be rotated 270 degrees clockwise
+    Rotate270,
+}
+
 /// An enum for bilateral filter algorithms
 // Read a description of the algorithms here:
 // https://people.csail.mit.edu/sparis/publi/2009/fntcgv/Paris_09_Bilateral_filtering.pdf
@@ -41,6 +41,16 @@ pub(crate) fn check_non_neg<T: Number>(val: T, name: &str) -> ImgProcResult<()>
     Ok(())
 }
 
+pub(crate) fn check_positive<T: Number>(val: T, name: &str) -> ImgProcResult<()> {
+    // Multiplying by zero only gives a nonzero result for NaN and infinite floats, so this also
+    // rejects non-finite values
+    if val <= 0.into() || val * 0.into() != 0.into() {
+        return Err(ImgProcError::InvalidArgError(format!("{} must be positive and finite", name)));
+    }
+
+    Ok(())
+}
+
 pub(crate) fn check_equal<T: std::cmp::PartialEq>(val_1: T, val_2: T, name: &str) -> ImgProcResult<()> {
     if val_1 != val_2 {
         return Err(ImgProcError::InvalidArgError(format!("{} must be equal", name)));
@@ -49,6 +59,14 @@ pub(crate) fn check_equal<T: std::cmp::PartialEq>(val_1: T, val_2: T, name: &str
     Ok(())
 }
 
+pub(crate) fn check_dimensions_equal(expected: (u32, u32), found: (u32, u32)) -> ImgProcResult<()> {
+    if expected != found {
+        return Err(ImgProcError::DimensionMismatch { expected, found });
+    }
+
+    Ok(())
+}
+
 pub(crate) fn check_square(val: f64, name: &str) -> ImgProcResult<()> {
     if val.sqrt() % 1.0 != 0.0 {
         return Err(ImgProcError::InvalidArgError(format!("{} must be square", name)));
@@ -71,4 +89,36 @@ pub(crate) fn check_grayscale<T: Number>(input: &Image<T>) -> ImgProcResult<()>
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+pub(crate) fn check_rgb<T: Number>(input: &Image<T>) -> ImgProcResult<()> {
+    if input.info().channels_non_alpha() != 3 {
+        return Err(ImgProcError::InvalidArgError("input is not an RGB image".to_string()));
+    }
+
+    Ok(())
+}
+
+pub(crate) fn check_non_empty<T: Number>(input: &Image<T>) -> ImgProcResult<()> {
+    if input.info().width == 0 || input.info().height == 0 {
+        return Err(ImgProcError::InvalidArgError("input image is empty".to_string()));
+    }
+
+    Ok(())
+}
+
+pub(crate) fn check_non_empty_slice<T>(slice: &[T], name: &str) -> ImgProcResult<()> {
+    if slice.is_empty() {
+        return Err(ImgProcError::InvalidArgError(format!("{} must not be empty", name)));
+    }
+
+    Ok(())
+}
+
+pub(crate) fn check_alpha<T: Number>(input: &Image<T>) -> ImgProcResult<()> {
+    if !input.info().alpha {
+        return Err(ImgProcError::InvalidArgError("input does not have an alpha channel".to_string()));
+    }
+
+    Ok(())
+}
@@ -18,6 +18,24 @@ pub enum ImgProcError {
     InvalidArgError(String),
     NumericError(String),
     RulinalgError(rulinalg::error::Error),
+    /// A `(width, height)` pair, such as the size of a kernel or a second image, did not match
+    /// the dimensions that were expected
+    DimensionMismatch { expected: (u32, u32), found: (u32, u32) },
+    /// The operation was stopped early because its cancellation flag was set
+    Cancelled,
+}
+
+impl std::fmt::Display for ImgProcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImgProcError::InvalidArgError(msg) => write!(f, "invalid argument: {}", msg),
+            ImgProcError::NumericError(msg) => write!(f, "numeric error: {}", msg),
+            ImgProcError::RulinalgError(err) => write!(f, "linear algebra error: {}", err),
+            ImgProcError::DimensionMismatch { expected, found } =>
+                write!(f, "dimension mismatch: expected {}x{}, found {}x{}", expected.0, expected.1, found.0, found.1),
+            ImgProcError::Cancelled => write!(f, "operation cancelled"),
+        }
+    }
 }
 
 impl From<rulinalg::error::Error> for ImgProcError {
@@ -34,6 +52,8 @@ pub enum ImgIoError {
     IoError(io::Error),
     ImageReaderError(image::error::ImageError),
     ImageWriteError(String),
+    /// An image processing operation failed while processing a file
+    ProcessingError(ImgProcError),
     OtherError(String),
 }
 
@@ -49,6 +69,12 @@ impl From<image::error::ImageError> for ImgIoError {
     }
 }
 
+impl From<ImgProcError> for ImgIoError {
+    fn from(err: ImgProcError) -> Self {
+        ImgIoError::ProcessingError(err)
+    }
+}
+
 impl From<String> for ImgIoError {
     fn from(err: String) -> Self {
         ImgIoError::OtherError(err)
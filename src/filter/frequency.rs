@@ -0,0 +1,238 @@
+use crate::error;
+use crate::enums::{FrequencyFilter, Rolloff};
+use crate::error::{ImgProcResult, ImgProcError};
+use crate::image::{BaseImage, Image};
+
+use std::f64::consts::PI;
+
+/// Filters a single-channel image in the frequency domain. Frequencies are measured in cycles per
+/// pixel, so the highest frequency along each axis is 0.5
+pub fn frequency_filter(input: &Image<f64>, filter: FrequencyFilter, rolloff: Rolloff) -> ImgProcResult<Image<f64>> {
+    error::check_non_empty(input)?;
+    if input.info().channels != 1 {
+        return Err(ImgProcError::InvalidArgError("input is not a single-channel image".to_string()));
+    }
+
+    match filter {
+        FrequencyFilter::Lowpass(cutoff) | FrequencyFilter::Highpass(cutoff) => {
+            error::check_positive(cutoff, "cutoff")?;
+        },
+        FrequencyFilter::Bandpass(low, high) => {
+            error::check_positive(low, "low")?;
+            error::check_positive(high, "high")?;
+            if low >= high {
+                return Err(ImgProcError::InvalidArgError("low must be less than high".to_string()));
+            }
+        },
+    }
+    if let Rolloff::Butterworth(order) = rolloff {
+        error::check_positive(order, "order")?;
+    }
+
+    let (width, height) = input.info().wh();
+    let mut data: Vec<(f64, f64)> = input.data().iter().map(|val| (*val, 0.0)).collect();
+    fft_2d(&mut data, width as usize, height as usize, false);
+
+    for v in 0..height {
+        for u in 0..width {
+            let f_u = wrap_frequency(u, width);
+            let f_v = wrap_frequency(v, height);
+            let dist = (f_u * f_u + f_v * f_v).sqrt();
+
+            let gain = match filter {
+                FrequencyFilter::Lowpass(cutoff) => lowpass_gain(dist, cutoff, &rolloff),
+                FrequencyFilter::Highpass(cutoff) => 1.0 - lowpass_gain(dist, cutoff, &rolloff),
+                FrequencyFilter::Bandpass(low, high) => {
+                    lowpass_gain(dist, high, &rolloff) * (1.0 - lowpass_gain(dist, low, &rolloff))
+                },
+            };
+
+            let val = &mut data[(v * width + u) as usize];
+            val.0 *= gain;
+            val.1 *= gain;
+        }
+    }
+
+    fft_2d(&mut data, width as usize, height as usize, true);
+
+    Ok(Image::from_vec(width, height, 1, false, data.iter().map(|val| val.0).collect()))
+}
+
+// Converts a DFT index into a signed frequency in cycles per pixel
+fn wrap_frequency(i: u32, len: u32) -> f64 {
+    if i <= len / 2 {
+        i as f64 / len as f64
+    } else {
+        (i as f64 - len as f64) / len as f64
+    }
+}
+
+// The gain of a lowpass filter with cutoff frequency `cutoff` at frequency `dist`
+fn lowpass_gain(dist: f64, cutoff: f64, rolloff: &Rolloff) -> f64 {
+    match rolloff {
+        Rolloff::Ideal => if dist <= cutoff { 1.0 } else { 0.0 },
+        Rolloff::Butterworth(order) => 1.0 / (1.0 + (dist / cutoff).powi(2 * *order as i32)),
+    }
+}
+
+// Computes the 2D discrete Fourier transform of `data` in place, or its inverse if `inverse`
+fn fft_2d(data: &mut [(f64, f64)], width: usize, height: usize, inverse: bool) {
+    let row_plan = FftPlan::new(width, inverse);
+    for row in data.chunks_mut(width) {
+        row_plan.apply(row);
+    }
+
+    let col_plan = FftPlan::new(height, inverse);
+    let mut col = vec![(0.0, 0.0); height];
+    for x in 0..width {
+        for (y, val) in col.iter_mut().enumerate() {
+            *val = data[y * width + x];
+        }
+
+        col_plan.apply(&mut col);
+
+        for (y, val) in col.iter().enumerate() {
+            data[y * width + x] = *val;
+        }
+    }
+}
+
+// A precomputed 1D discrete Fourier transform of a fixed length. Lengths that are a power of two
+// use a radix-2 FFT directly; other lengths use Bluestein's algorithm, which rewrites the DFT as a
+// convolution with a chirp and evaluates it with a radix-2 FFT of at least twice the length
+struct FftPlan {
+    len: usize,
+    inverse: bool,
+    twiddles: Vec<(f64, f64)>,
+    bluestein: Option<Bluestein>,
+}
+
+// The chirp `exp(+-i * pi * k^2 / len)` and the FFT of its zero-padded conjugate
+struct Bluestein {
+    chirp: Vec<(f64, f64)>,
+    kernel: Vec<(f64, f64)>,
+    twiddles: Vec<(f64, f64)>,
+}
+
+impl FftPlan {
+    fn new(len: usize, inverse: bool) -> Self {
+        let sign = if inverse { 1.0 } else { -1.0 };
+
+        if len.is_power_of_two() {
+            return FftPlan { len, inverse, twiddles: twiddles(len, sign), bluestein: None };
+        }
+
+        let chirp: Vec<(f64, f64)> = (0..len)
+            .map(|k| {
+                // k^2 is reduced modulo 2 * len to keep the angle small
+                let angle = sign * PI * ((k * k) % (2 * len)) as f64 / len as f64;
+                (angle.cos(), angle.sin())
+            })
+            .collect();
+
+        let padded_len = (2 * len - 1).next_power_of_two();
+        let forward = twiddles(padded_len, -1.0);
+        let mut kernel = vec![(0.0, 0.0); padded_len];
+        kernel[0] = conj(chirp[0]);
+        for k in 1..len {
+            kernel[k] = conj(chirp[k]);
+            kernel[padded_len - k] = conj(chirp[k]);
+        }
+        radix2(&mut kernel, &forward);
+
+        FftPlan {
+            len,
+            inverse,
+            twiddles: Vec::new(),
+            bluestein: Some(Bluestein { chirp, kernel, twiddles: forward }),
+        }
+    }
+
+    // Computes the transform of `data` in place
+    fn apply(&self, data: &mut [(f64, f64)]) {
+        match &self.bluestein {
+            None => radix2(data, &self.twiddles),
+            Some(b) => {
+                let padded_len = b.kernel.len();
+                let mut buf = vec![(0.0, 0.0); padded_len];
+                for ((val, x), w) in buf.iter_mut().zip(data.iter()).zip(b.chirp.iter()) {
+                    *val = mul(*x, *w);
+                }
+
+                radix2(&mut buf, &b.twiddles);
+                for (val, k) in buf.iter_mut().zip(b.kernel.iter()) {
+                    *val = conj(mul(*val, *k));
+                }
+
+                // Inverse FFT of the product, computed as the conjugate of a forward FFT
+                radix2(&mut buf, &b.twiddles);
+                for ((x, val), w) in data.iter_mut().zip(buf.iter()).zip(b.chirp.iter()) {
+                    let c = conj(*val);
+                    *x = mul((c.0 / padded_len as f64, c.1 / padded_len as f64), *w);
+                }
+            },
+        }
+
+        if self.inverse {
+            for val in data.iter_mut() {
+                val.0 /= self.len as f64;
+                val.1 /= self.len as f64;
+            }
+        }
+    }
+}
+
+// The twiddle factors `exp(sign * 2 * pi * i * k / len)` for `k` in `0..len / 2`
+fn twiddles(len: usize, sign: f64) -> Vec<(f64, f64)> {
+    (0..(len / 2))
+        .map(|k| {
+            let angle = sign * 2.0 * PI * k as f64 / len as f64;
+            (angle.cos(), angle.sin())
+        })
+        .collect()
+}
+
+// Computes the unnormalized radix-2 FFT of `data` in place, whose length must be a power of two,
+// using the twiddle factors for that length
+fn radix2(data: &mut [(f64, f64)], twiddles: &[(f64, f64)]) {
+    let n = data.len();
+
+    // Bit reversal permutation
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+
+        if i < j {
+            data.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let stride = n / len;
+        for start in (0..n).step_by(len) {
+            for k in 0..(len / 2) {
+                let t = mul(data[start + k + len / 2], twiddles[k * stride]);
+                let a = data[start + k];
+
+                data[start + k] = (a.0 + t.0, a.1 + t.1);
+                data[start + k + len / 2] = (a.0 - t.0, a.1 - t.1);
+            }
+        }
+
+        len <<= 1;
+    }
+}
+
+fn mul(a: (f64, f64), b: (f64, f64)) -> (f64, f64) {
+    (a.0 * b.0 - a.1 * b.1, a.0 * b.1 + a.1 * b.0)
+}
+
+fn conj(a: (f64, f64)) -> (f64, f64) {
+    (a.0, -a.1)
+}
@@ -1,50 +1,165 @@
 use crate::error;
 use crate::error::{ImgProcResult, ImgProcError};
-use crate::image::{Image, BaseImage};
+use crate::image::{Number, Image, BaseImage};
 
-use std::cmp::{Ordering, Reverse};
+use std::cmp::Ordering;
+use std::sync::atomic::{self, AtomicBool};
 
 /// Applies a median filter, where each output pixel is the median of the pixels in a
 /// `(2 * radius + 1) x (2 * radius + 1)` kernel in the input image. Based on Ben Weiss' partial
 /// histogram method, using a tier radix of 2. A detailed description can be found
 /// [here](http://citeseerx.ist.psu.edu/viewdoc/download?doi=10.1.1.93.1608&rep=rep1&type=pdf).
+///
+/// Pixels outside the image are treated as copies of the nearest edge pixel, so `radius` may
+/// be larger than the image dimensions.
 pub fn median_filter(input: &Image<u8>, radius: u32) -> ImgProcResult<Image<u8>> {
-    let mut n_cols = (4.0 * (radius as f64).powf(2.0 / 3.0)).floor() as usize;
-    if n_cols % 2 == 0 {
-        n_cols += 1;
-    }
+    median_filter_monitored(input, radius, None, None)
+}
+
+/// Applies a median filter in the same way as [`median_filter`](fn.median_filter.html). Before
+/// each band of columns is processed, `cancel` is checked and `ImgProcError::Cancelled` is
+/// returned if it is set; after each band, `progress` is called with the fraction of the image
+/// completed (in the range [0, 1])
+pub fn median_filter_monitored(input: &Image<u8>, radius: u32, progress: Option<&dyn Fn(f32)>,
+                               cancel: Option<&AtomicBool>) -> ImgProcResult<Image<u8>> {
+    error::check_non_empty(input)?;
 
+    let n_cols = get_n_cols(radius);
+    let width = input.info().width;
     let mut output = Image::blank(input.info());
 
-    for x in (0..output.info().width).step_by(n_cols) {
+    for x in (0..width).step_by(n_cols) {
+        check_cancelled(cancel)?;
         process_cols_med(input, &mut output, radius, n_cols, x);
+        report_progress(progress, x, n_cols, width);
     }
 
     Ok(output)
 }
 
+/// Applies a median filter, where each output pixel is the median of the pixels in a
+/// `(2 * radius + 1) x (2 * radius + 1)` kernel in the input image. Each kernel is collected and
+/// sorted directly, which is slower than [`median_filter`](fn.median_filter.html) for large images
+/// but can be faster for very small images or large radii.
+///
+/// Pixels outside the image are treated as copies of the nearest edge pixel.
+pub fn median_filter_naive(input: &Image<u8>, radius: u32) -> ImgProcResult<Image<u8>> {
+    error::check_non_empty(input)?;
+
+    Ok(median_filter_sorted(input, radius))
+}
+
+/// Applies a median filter to an `f64` image, where each output pixel is the median of the pixels
+/// in a `(2 * radius + 1) x (2 * radius + 1)` kernel in the input image. Each kernel is collected
+/// and sorted directly, since histograms do not apply to continuous values.
+///
+/// Pixels outside the image are treated as copies of the nearest edge pixel.
+pub fn median_filter_f64(input: &Image<f64>, radius: u32) -> ImgProcResult<Image<f64>> {
+    error::check_non_empty(input)?;
+
+    Ok(median_filter_sorted(input, radius))
+}
+
 /// Applies an alpha-trimmed mean filter, where each output pixel is the mean of the
 /// pixels in a `(2 * radius + 1) x (2 * radius + 1)` kernel in the input image, with the lowest
 /// `alpha / 2` pixels and the highest `alpha / 2` pixels removed.
+///
+/// The mean is rounded to the nearest integer. Because the kernel size is odd and `alpha` must be
+/// even, the number of remaining pixels is always odd, so the mean never lies exactly halfway
+/// between two integers and the choice of tie-breaking rule has no effect.
+///
+/// Pixels outside the image are treated as copies of the nearest edge pixel, so `radius` may
+/// be larger than the image dimensions.
 pub fn alpha_trimmed_mean_filter(input: &Image<u8>, radius: u32, alpha: u32) -> ImgProcResult<Image<u8>> {
+    alpha_trimmed_mean_filter_monitored(input, radius, alpha, None, None)
+}
+
+/// Applies an alpha-trimmed mean filter in the same way as
+/// [`alpha_trimmed_mean_filter`](fn.alpha_trimmed_mean_filter.html). Before each band of columns
+/// is processed, `cancel` is checked and `ImgProcError::Cancelled` is returned if it is set; after
+/// each band, `progress` is called with the fraction of the image completed (in the range [0, 1])
+pub fn alpha_trimmed_mean_filter_monitored(input: &Image<u8>, radius: u32, alpha: u32, progress: Option<&dyn Fn(f32)>,
+                                           cancel: Option<&AtomicBool>) -> ImgProcResult<Image<u8>> {
+    error::check_non_empty(input)?;
+
     let size = 2 * radius + 1;
     error::check_even(alpha, "alpha")?;
     if alpha >= (size * size) {
         return Err(ImgProcError::InvalidArgError(format!("invalid alpha: size is {}, but alpha is {}", size, alpha)));
     }
 
+    let n_cols = get_n_cols(radius);
+    let width = input.info().width;
+    let mut output = Image::blank(input.info());
+
+    for x in (0..width).step_by(n_cols) {
+        check_cancelled(cancel)?;
+        process_cols_mean(input, &mut output, radius, alpha, n_cols, x);
+        report_progress(progress, x, n_cols, width);
+    }
+
+    Ok(output)
+}
+
+/// Computes the per-pixel median of a stack of frames, where each output channel is the median of
+/// the corresponding channel across all frames. For an even number of frames, the two middle
+/// values are averaged and rounded. This removes transient noise and objects that only appear in
+/// a minority of frames. All frames must have the same dimensions and channels
+pub fn temporal_median(frames: &[Image<u8>]) -> ImgProcResult<Image<u8>> {
+    error::check_non_empty_slice(frames, "frames")?;
+
+    let first = &frames[0];
+    for frame in frames.iter().skip(1) {
+        error::check_equal(first.info(), frame.info(), "frame dimensions")?;
+    }
+
+    let n = frames.len();
+    let mut vals = Vec::with_capacity(n);
+    let data = (0..first.data().len())
+        .map(|i| {
+            vals.clear();
+            vals.extend(frames.iter().map(|frame| frame.data()[i]));
+            vals.sort_unstable();
+
+            if n % 2 == 0 {
+                (vals[n / 2 - 1] as u16 + vals[n / 2] as u16).div_ceil(2) as u8
+            } else {
+                vals[n / 2]
+            }
+        })
+        .collect();
+
+    let (width, height, channels, alpha) = first.info().whca();
+    Ok(Image::from_vec(width, height, channels, alpha, data))
+}
+
+// The number of columns processed at once, which is always odd
+fn get_n_cols(radius: u32) -> usize {
     let mut n_cols = (4.0 * (radius as f64).powf(2.0 / 3.0)).floor() as usize;
     if n_cols % 2 == 0 {
         n_cols += 1;
     }
 
-    let mut output = Image::blank(input.info());
+    n_cols
+}
 
-    for x in (0..output.info().width).step_by(n_cols) {
-        process_cols_mean(input, &mut output, radius, alpha, n_cols, x);
+// Returns `ImgProcError::Cancelled` if `cancel` is set
+fn check_cancelled(cancel: Option<&AtomicBool>) -> ImgProcResult<()> {
+    if let Some(flag) = cancel {
+        if flag.load(atomic::Ordering::Relaxed) {
+            return Err(ImgProcError::Cancelled);
+        }
     }
 
-    Ok(output)
+    Ok(())
+}
+
+// Reports the fraction of columns processed after the band starting at column `x`
+fn report_progress(progress: Option<&dyn Fn(f32)>, x: u32, n_cols: usize, width: u32) {
+    if let Some(f) = progress {
+        let done = (x as usize + n_cols).min(width as usize);
+        f(done as f32 / width as f32);
+    }
 }
 
 /*
@@ -212,6 +327,39 @@ impl MedianHist {
     }
 }
 
+fn median_filter_sorted<T: Number>(input: &Image<T>, radius: u32) -> Image<T> {
+    let (width, height, channels) = input.info().whc();
+    let size = 2 * radius + 1;
+    let mut output = Image::blank(input.info());
+    let mut p_out = Vec::with_capacity(channels as usize);
+    let mut kernel = Vec::with_capacity((size * size) as usize);
+
+    for y in 0..height {
+        for x in 0..width {
+            p_out.clear();
+
+            for c in 0..(channels as usize) {
+                kernel.clear();
+
+                for j in (y as i64 - radius as i64)..=(y as i64 + radius as i64) {
+                    for i in (x as i64 - radius as i64)..=(x as i64 + radius as i64) {
+                        let x_clamp = i.clamp(0, width as i64 - 1) as u32;
+                        let y_clamp = j.clamp(0, height as i64 - 1) as u32;
+                        kernel.push(input.get_pixel_unchecked(x_clamp, y_clamp)[c]);
+                    }
+                }
+
+                kernel.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+                p_out.push(kernel[kernel.len() / 2]);
+            }
+
+            output.set_pixel(x, y, &p_out);
+        }
+    }
+
+    output
+}
+
 fn process_cols_med(input: &Image<u8>, output: &mut Image<u8>, radius: u32, n_cols: usize, x: u32) {
     let size = 2 * radius + 1;
     let center = ((size * size) / 2 + 1) as i32; // Half the number of pixels in a kernel. If
@@ -288,8 +436,10 @@ fn init_cols_med(input: &Image<u8>, output: &mut Image<u8>, histograms: &mut Vec
             }
         }
 
-        let x_clamp = (x + i as u32).clamp(0, output.info().width - 1);
-        output.set_pixel(x_clamp, 0, &p_out);
+        // The last band of columns may extend past the right edge of the image
+        if x + (i as u32) < output.info().width {
+            output.set_pixel(x + i as u32, 0, &p_out);
+        }
 
         set_pivots_med(histograms, &p_out, i);
     }
@@ -304,11 +454,8 @@ fn process_row_med(output: &mut Image<u8>, histograms: &mut Vec<MedianHist>, p_o
                                                         // the previous median
 
             match sum.cmp(&center) {
-                Ordering::Equal => { // The current median is equal to the previous median
-                    p_out.push(pivot);
-                },
-                Ordering::Less => { // The current median is greater than the previous median,
-                                    // so the histogram should be scanned upwards
+                Ordering::Less => { // The current median is greater than or equal to the previous
+                                    // median, so the histogram should be scanned upwards
                     for key in pivot..=255 {
                         let add = hist.data().get_count(key as usize, i);
 
@@ -321,8 +468,10 @@ fn process_row_med(output: &mut Image<u8>, histograms: &mut Vec<MedianHist>, p_o
                         sum += add;
                     }
                 },
-                Ordering::Greater => { // The current median is less than the previous median, so the histogram
-                                       // should be scanned downwards
+                Ordering::Equal | Ordering::Greater => { // At least `center` values are less than the
+                                                         // previous median, so the current median is
+                                                         // less than it and the histogram should be
+                                                         // scanned downwards
                     for key in (0..pivot).rev() {
                         sum -= hist.data().get_count(key as usize, i);
 
@@ -336,8 +485,9 @@ fn process_row_med(output: &mut Image<u8>, histograms: &mut Vec<MedianHist>, p_o
             }
         }
 
-        let x_clamp = (x + i as u32).clamp(0, output.info().width - 1);
-        output.set_pixel(x_clamp, y, &p_out);
+        if x + (i as u32) < output.info().width {
+            output.set_pixel(x + i as u32, y, &p_out);
+        }
 
         set_pivots_med(histograms, &p_out, i);
     }
@@ -368,197 +518,72 @@ fn set_pivots_med(histograms: &mut Vec<MedianHist>, pivots: &[u8], index: usize)
 #[derive(Debug, Clone)]
 struct MeanHist {
     data: PartialHistograms,
-    sums: Vec<i32>, // The sum of all the participating pixel values in the kernel for each pixel
-    lower: Vec<Vec<u8>>, // Vectors of all the lowest discarded pixel values in the kernel
-                         // for each pixel
-    upper: Vec<Vec<u8>>, // Vectors of all the highest discarded pixel values in the kernel for
-                         // each pixel
-    trim: usize, // The number of pixel values discarded at the low and high ends of each kernel
-                 // (equal to half of alpha)
-    len: f32, // The number of participating pixel values in each kernel
+    sums: Vec<i32>, // The sum of all the pixel values in the kernel for each pixel
+    trim: i32, // The number of pixel values discarded at the low and high ends of each kernel
+               // (equal to half of alpha)
+    len: i32, // The number of participating pixel values in each kernel
 }
 
 impl MeanHist {
     fn new(radius: usize, n_cols: usize, alpha: u32) -> Self {
         let size = 2 * radius + 1;
-        let len = ((size * size) - alpha as usize) as f32;
+        let len = ((size * size) - alpha as usize) as i32;
 
         MeanHist {
             data: PartialHistograms::new(radius, n_cols),
             sums: Vec::with_capacity(n_cols),
-            lower: Vec::with_capacity(n_cols),
-            upper: Vec::with_capacity(n_cols),
-            trim: (alpha as usize) / 2,
+            trim: (alpha as i32) / 2,
             len,
         }
     }
 
-    fn data(&self) -> &PartialHistograms {
-        &self.data
-    }
-
     fn init(&mut self) {
-        self.sums = vec![0; self.data.n_cols];
-        self.lower = vec![Vec::with_capacity(self.trim); self.data.n_cols];
-        self.upper = vec![Vec::with_capacity(self.trim); self.data.n_cols];
+        self.sums = (0..self.data.n_cols)
+            .map(|n| (0..256).map(|key| key as i32 * self.data.get_count(key, n)).sum())
+            .collect();
     }
 
-    // By some miracle, this seems to work!
     fn update(&mut self, p_in: &[&[u8]], channel_index: usize, add: bool) {
         if !self.sums.is_empty() {
-            if add {
-                for n in 0..self.data.n_cols {
-                    for i in n..(n + self.data.size) {
-                        let val = p_in[i][channel_index];
-                        let lower = self.lower(n);
-                        let upper = self.upper(n);
-
-                        if val < lower {
-                            self.lower[n].remove(self.trim -  1);
-                            self.sums[n] += lower as i32;
-
-                            let pos = self.lower[n].binary_search(&val).unwrap_or_else(|e| e);
-                            self.lower[n].insert(pos, val);
-                        } else if val > upper {
-                            self.upper[n].remove(self.trim - 1);
-                            self.sums[n] += upper as i32;
-
-                            let pos = self.lower[n].binary_search_by_key(&Reverse(&val), Reverse).unwrap_or_else(|e| e);
-                            self.upper[n].insert(pos, val);
-                        } else {
-                            self.sums[n] += val as i32;
-                        }
-                    }
-                }
-                self.data.update(p_in, channel_index, add);
-            } else {
-                self.data.update(p_in, channel_index, add);
-                for n in 0..self.data.n_cols {
-                    for i in n..(n + self.data.size) {
-                        let val = p_in[i][channel_index];
-                        let lower = self.lower(n);
-                        let upper = self.upper(n);
-
-                        let mut lower_count = self.data.get_count(lower as usize, n);
-                        let mut upper_count = self.data.get_count(upper as usize, n);
-
-                        for j in i..(n + self.data.size) {
-                            if p_in[j][channel_index] == lower {
-                                lower_count += 1;
-                            } else if p_in[j][channel_index] == upper {
-                                upper_count += 1;
-                            }
-                        }
-
-                        for j in self.lower[n].iter().rev() {
-                            if *j == lower {
-                                lower_count -= 1;
-                            } else {
-                                break;
-                            }
-                        }
+            let inc = if add { 1 } else { -1 };
 
-                        for j in self.upper[n].iter().rev() {
-                            if *j == upper {
-                                upper_count -= 1;
-                            } else {
-                                break;
-                            }
-                        }
-
-                        if val == lower && lower_count == 0 {
-                            self.lower[n].remove(self.trim - 1);
-                            self.get_next_lower(n, lower_count, lower);
-                        } else if val < lower {
-                            let res = self.lower[n].binary_search(&val);
-
-                            match res {
-                                Ok(pos) => {
-                                    self.lower[n].remove(pos);
-                                    self.get_next_lower(n, lower_count, lower);
-                                },
-                                Err(_) => {
-                                    self.sums[n] -= val as i32;
-                                }
-                            }
-                        } else if val == upper && upper_count == 0 {
-                            self.upper[n].remove(self.trim - 1);
-                            self.get_next_upper(n, upper_count, upper);
-                        } else if val > upper {
-                            let res = self.lower[n].binary_search_by_key(&Reverse(&val), Reverse);
-
-                            match res {
-                                Ok(pos) => {
-                                    self.upper[n].remove(pos);
-                                    self.get_next_upper(n, upper_count, upper);
-                                },
-                                Err(_) => {
-                                    self.sums[n] -= val as i32;
-                                }
-                            }
-                        } else {
-                            self.sums[n] -= val as i32;
-                        }
-                    }
+            for n in 0..self.data.n_cols {
+                for p in p_in[n..(n + self.data.size)].iter() {
+                    self.sums[n] += inc * p[channel_index] as i32;
                 }
             }
-        } else {
-            self.data.update(p_in, channel_index, add);
         }
-    }
 
-    fn set_sum(&mut self, sum: i32, index: usize) {
-        self.sums[index] = sum;
+        self.data.update(p_in, channel_index, add);
     }
 
-    fn set_upper(&mut self, vals: Vec<u8>, index: usize) {
-        self.upper[index] = vals;
-    }
+    // Returns the sum of the `trim` lowest (or highest) pixel values in the kernel
+    fn trimmed_sum<I: Iterator<Item = usize>>(&self, index: usize, keys: I) -> i32 {
+        let mut remaining = self.trim;
+        let mut sum = 0;
 
-    fn set_lower(&mut self, vals: Vec<u8>, index: usize) {
-        self.lower[index] = vals;
-    }
+        for key in keys {
+            if remaining == 0 {
+                break;
+            }
 
-    fn upper(&self, index: usize) -> u8 {
-        self.upper[index][self.trim-1]
-    }
+            let count = self.data.get_count(key, index).min(remaining);
+            sum += count * key as i32;
+            remaining -= count;
+        }
 
-    fn lower(&self, index: usize) -> u8 {
-        self.lower[index][self.trim-1]
+        sum
     }
 
+    // Rounds the mean to the nearest integer using integer arithmetic, so the result is exact
+    // regardless of the kernel size
     fn get_mean(&self, index: usize) -> u8 {
-        ((self.sums[index] as f32) / self.len).round() as u8
-    }
-
-    fn get_next_lower(&mut self, n: usize, lower_count: i32, lower: u8) {
-        if lower_count > 0 {
-            self.lower[n].push(lower);
-            self.sums[n] -= lower as i32;
-        } else {
-            for key in (lower + 1)..=255 {
-                if self.data.get_count(key as usize, n) > 0 {
-                    self.lower[n].push(key);
-                    self.sums[n] -= key as i32;
-                    break;
-                }
-            }
+        let mut sum = self.sums[index];
+        if self.trim > 0 {
+            sum -= self.trimmed_sum(index, 0..256) + self.trimmed_sum(index, (0..256).rev());
         }
-    }
 
-    fn get_next_upper(&mut self, n: usize, upper_count: i32, upper: u8) {
-        if upper_count > 0 {
-            self.upper[n].push(upper);
-            self.sums[n] -= upper as i32;
-        } else {
-            for key in (0..upper).rev() {
-                if self.data.get_count(key as usize, n) > 0 {
-                    self.upper[n].push(key);
-                    self.sums[n] -= key as i32;
-                    break;
-                }
-            }
-        }
+        ((2 * sum + self.len) / (2 * self.len)) as u8
     }
 }
 
@@ -568,7 +593,7 @@ fn process_cols_mean(input: &Image<u8>, output: &mut Image<u8>, radius: u32, alp
     let mut p_out = Vec::with_capacity(channels as usize);
 
     // Initialize histogram and process first row
-    init_cols_mean(input, output, &mut histograms, &mut p_out, radius, alpha, n_cols, x);
+    init_cols_mean(input, output, &mut histograms, &mut p_out, radius, n_cols, x);
 
     // Update histogram and process remaining rows
     let mut row_in = Vec::with_capacity(n_cols);
@@ -595,9 +620,8 @@ fn process_cols_mean(input: &Image<u8>, output: &mut Image<u8>, radius: u32, alp
 }
 
 fn init_cols_mean(input: &Image<u8>, output: &mut Image<u8>, histograms: &mut Vec<MeanHist>,
-                  p_out: &mut Vec<u8>, radius: u32, alpha: u32, n_cols: usize, x: u32) {
+                  p_out: &mut Vec<u8>, radius: u32, n_cols: usize, x: u32) {
     let (width, height) = input.info().wh();
-    let size = 2 * radius + 1;
 
     // Initialize histograms
     let mut row_in = Vec::with_capacity(n_cols);
@@ -608,6 +632,7 @@ fn init_cols_mean(input: &Image<u8>, output: &mut Image<u8>, histograms: &mut Ve
         }
 
         add_row_mean(histograms, &row_in);
+        row_in.clear();
     }
 
     // Initialize histograms
@@ -616,43 +641,16 @@ fn init_cols_mean(input: &Image<u8>, output: &mut Image<u8>, histograms: &mut Ve
     }
 
     // Compute first mean values
-    let trim = (alpha as usize) / 2;
-    let upper_trim = (size * size) as usize - trim;
     for i in 0..n_cols {
         p_out.clear();
-        for hist in histograms.iter_mut() {
-            let mut count = 0;
-            let mut sum = 0;
-            let mut upper = Vec::with_capacity(trim);
-            let mut lower = Vec::with_capacity(trim);
-
-            for key in 0u8..=255 {
-                let mut add = hist.data().get_count(key as usize, i);
-                count += add;
-                sum += add * key as i32;
-
-                while lower.len() < trim && add > 0 {
-                    lower.push(key);
-                    sum -= key as i32;
-                    add -= 1;
-                }
-
-                while (count as usize) > upper_trim && upper.len() < trim && add > 0 {
-                    upper.insert(0, key);
-                    sum -= key as i32;
-                    add -= 1;
-                }
-            }
-
-            hist.set_sum(sum, i);
-            hist.set_upper(upper, i);
-            hist.set_lower(lower, i);
-
+        for hist in histograms.iter() {
             p_out.push(hist.get_mean(i));
         }
 
-        let x_clamp = (x + i as u32).clamp(0, output.info().width - 1);
-        output.set_pixel(x_clamp, 0, &p_out);
+        // The last band of columns may extend past the right edge of the image
+        if x + (i as u32) < output.info().width {
+            output.set_pixel(x + i as u32, 0, &p_out);
+        }
     }
 }
 
@@ -663,8 +661,9 @@ fn process_row_mean(output: &mut Image<u8>, histograms: &mut Vec<MeanHist>, p_ou
             p_out.push(hist.get_mean(i));
         }
 
-        let x_clamp = (x + i as u32).clamp(0, output.info().width - 1);
-        output.set_pixel(x_clamp, y, &p_out);
+        if x + (i as u32) < output.info().width {
+            output.set_pixel(x + i as u32, y, &p_out);
+        }
     }
 }
 
@@ -2,29 +2,44 @@
 
 pub use self::bilateral::*;
 pub use self::edge::*;
+pub use self::frequency::*;
 pub use self::median::*;
+pub use self::pyramid::*;
 
 mod median;
 mod bilateral;
 mod edge;
-
-use crate::{error, util};
-use crate::enums::Thresh;
-use crate::error::ImgProcResult;
-use crate::image::{BaseImage, Image, Number};
+mod frequency;
+mod pyramid;
+
+use crate::{analysis, error, util};
+use crate::enums::{ConvMode, Padding, Thresh};
+use crate::error::{ImgProcResult, ImgProcError};
+use crate::image::{BaseImage, Image, ImageInfo, Number, Rect, SubImage};
+use crate::util::Kernel;
 use crate::util::constants::{K_SHARPEN, K_UNSHARP_MASKING};
 
 #[cfg(feature = "rayon")]
 use rayon::prelude::*;
 
+// Number of pixels of context kept around the region passed to `apply_in_region`
+const REGION_MARGIN: u32 = 16;
+
 /////////////////////
 // Linear filtering
 /////////////////////
 
 /// Applies a 1D filter. If `is_vert` is true, applies `kernel`
 /// as a vertical filter; otherwise applies `kernel` as a horizontal filter
-#[cfg(not(feature = "rayon"))]
 pub fn filter_1d(input: &Image<f64>, kernel: &[f64], is_vert: bool) -> ImgProcResult<Image<f64>> {
+    filter_1d_padded(input, kernel, is_vert, Padding::Clamp)
+}
+
+/// Applies a 1D filter, handling pixels beyond the edges of `input` according to `padding`. If
+/// `is_vert` is true, applies `kernel` as a vertical filter; otherwise applies `kernel` as a
+/// horizontal filter
+#[cfg(not(feature = "rayon"))]
+pub fn filter_1d_padded(input: &Image<f64>, kernel: &[f64], is_vert: bool, padding: Padding) -> ImgProcResult<Image<f64>> {
     error::check_odd(kernel.len(), "kernel length")?;
 
     let (width, height, channels) = input.info().whc();
@@ -33,8 +48,8 @@ pub fn filter_1d(input: &Image<f64>, kernel: &[f64], is_vert: bool) -> ImgProcRe
 
     for y in 0..height {
         for x in 0..width {
-            util::apply_1d_kernel(&input.get_neighborhood_1d(x, y, kernel.len() as u32, is_vert),
-                                              &mut p_out, kernel)?;
+            util::apply_1d_kernel(&neighborhood_1d(input, x, y, kernel.len() as u32, is_vert, &padding),
+                                  &mut p_out, kernel)?;
             output.set_pixel(x, y, &p_out);
         }
     }
@@ -42,10 +57,11 @@ pub fn filter_1d(input: &Image<f64>, kernel: &[f64], is_vert: bool) -> ImgProcRe
     Ok(output)
 }
 
-/// Applies a 1D filter. If `is_vert` is true, applies `kernel`
-/// as a vertical filter; otherwise applies `kernel` as a horizontal filter
+/// Applies a 1D filter, handling pixels beyond the edges of `input` according to `padding`. If
+/// `is_vert` is true, applies `kernel` as a vertical filter; otherwise applies `kernel` as a
+/// horizontal filter
 #[cfg(feature = "rayon")]
-pub fn filter_1d(input: &Image<f64>, kernel: &[f64], is_vert: bool) -> ImgProcResult<Image<f64>> {
+pub fn filter_1d_padded(input: &Image<f64>, kernel: &[f64], is_vert: bool, padding: Padding) -> ImgProcResult<Image<f64>> {
     error::check_odd(kernel.len(), "kernel length")?;
 
     let (width, height, channels, alpha) = input.info().whca();
@@ -54,7 +70,7 @@ pub fn filter_1d(input: &Image<f64>, kernel: &[f64], is_vert: bool) -> ImgProcRe
         .into_par_iter()
         .map(|i| {
             let (x, y) = util::get_2d_coords(i, width);
-            util::apply_1d_kernel(&input.get_neighborhood_1d(x, y,kernel.len() as u32, is_vert), kernel).unwrap()
+            util::apply_1d_kernel(&neighborhood_1d(input, x, y, kernel.len() as u32, is_vert, &padding), kernel).unwrap()
         })
         .collect();
 
@@ -63,12 +79,26 @@ pub fn filter_1d(input: &Image<f64>, kernel: &[f64], is_vert: bool) -> ImgProcRe
 
 /// Applies a separable linear filter by first applying `vert_kernel` and then `horz_kernel`
 pub fn separable_filter(input: &Image<f64>, vert_kernel: &[f64], horz_kernel: &[f64]) -> ImgProcResult<Image<f64>> {
+    separable_filter_padded(input, vert_kernel, horz_kernel, Padding::Clamp)
+}
+
+/// Applies a separable linear filter by first applying `vert_kernel` and then `horz_kernel`,
+/// handling pixels beyond the edges of `input` according to `padding`
+pub fn separable_filter_padded(input: &Image<f64>, vert_kernel: &[f64], horz_kernel: &[f64], padding: Padding) -> ImgProcResult<Image<f64>> {
     error::check_odd(vert_kernel.len(), "vert_kernel length")?;
     error::check_odd(horz_kernel.len(), "horz_kernel length")?;
     error::check_equal(vert_kernel.len(), horz_kernel.len(), "kernel lengths")?;
 
-    let vertical = filter_1d(input, vert_kernel, true)?;
-    Ok(filter_1d(&vertical, horz_kernel, false)?)
+    let vertical = filter_1d_padded(input, vert_kernel, true, padding)?;
+    filter_1d_padded(&vertical, horz_kernel, false, padding)
+}
+
+// Returns the 1D neighborhood of `(x, y)` using the edge handling specified by `padding`
+fn neighborhood_1d<'a>(input: &'a Image<f64>, x: u32, y: u32, size: u32, is_vert: bool, padding: &Padding) -> SubImage<'a, f64> {
+    match padding {
+        Padding::Clamp => input.get_neighborhood_1d(x, y, size, is_vert),
+        Padding::Reflect => input.get_neighborhood_1d_reflect(x, y, size, is_vert),
+    }
 }
 
 /// Applies an unseparable linear filter
@@ -117,13 +147,57 @@ pub fn linear_filter(input: &Image<f64>, kernel: &[f64]) -> ImgProcResult<Image<
     error::check_odd(kernel.len(), "kernel length")?;
     error::check_square(kernel.len() as f64, "kernel length")?;
 
-    let separable = util::separate_kernel(kernel);
+    let separable = util::separate_kernel(kernel)?;
     match separable {
         Some((vert, horz)) => Ok(separable_filter(input, &vert, &horz)?),
         None => Ok(unseparable_filter(input, &kernel)?)
     }
 }
 
+/// Applies a linear filter using `kernel`, producing an output of the dimensions specified by
+/// `mode`
+pub fn convolve(input: &Image<f64>, kernel: &Kernel, mode: ConvMode) -> ImgProcResult<Image<f64>> {
+    match mode {
+        ConvMode::Same => linear_filter(input, kernel.data()),
+        ConvMode::Valid => {
+            let size = kernel.size();
+            let (width, height, channels, alpha) = input.info().whca();
+            if width < size || height < size {
+                return Err(ImgProcError::InvalidArgError(format!("invalid kernel size: image is {}x{}, \
+                    but kernel size is {}", width, height, size)));
+            }
+
+            let mut output = Image::blank(ImageInfo::new(width - size + 1, height - size + 1, channels, alpha));
+            let mut p_out = vec![0.0; channels as usize];
+
+            for y in 0..output.info().height {
+                for x in 0..output.info().width {
+                    p_out.iter_mut().for_each(|val| *val = 0.0);
+
+                    let neighborhood = input.subimage(x, y, size, size)?;
+                    for (k, p_in) in kernel.data().iter().zip(neighborhood.data().iter()) {
+                        for (val, channel) in p_out.iter_mut().zip(p_in.iter()) {
+                            *val += k * channel;
+                        }
+                    }
+
+                    output.set_pixel(x, y, &p_out);
+                }
+            }
+
+            Ok(output)
+        }
+    }
+}
+
+/// Applies a Gabor filter, which responds to texture with wavelength `wavelength` (in pixels)
+/// oscillating along the direction `orientation` (in degrees). `sigma` is the standard deviation
+/// of the Gaussian envelope and `aspect` is its spatial aspect ratio
+pub fn gabor(input: &Image<u8>, wavelength: f64, orientation: f64, sigma: f64, aspect: f64) -> ImgProcResult<Image<f64>> {
+    let kernel = Kernel::gabor(wavelength, orientation, sigma, aspect)?;
+    unseparable_filter(&input.clone().into(), kernel.data())
+}
+
 //////////////
 // Blurring
 //////////////
@@ -135,7 +209,14 @@ pub fn box_filter(input: &Image<f64>, size: u32) -> ImgProcResult<Image<f64>> {
     let len = (size * size) as usize;
     let kernel = vec![1.0 / ((size * size) as f64); len];
 
-    Ok(separable_filter(input, &kernel, &kernel)?)
+    separable_filter(input, &kernel, &kernel)
+}
+
+/// Applies a normalized box filter using a `size x size` kernel. If `input` has an alpha channel,
+/// the color channels are premultiplied by alpha while filtering, so that the colors of
+/// transparent pixels do not bleed into their neighbors
+pub fn box_filter_premultiplied(input: &Image<f64>, size: u32) -> ImgProcResult<Image<f64>> {
+    util::apply_premultiplied(input, |img| box_filter(img, size))
 }
 
 /// Applies a weighted average filter using a `size x size` kernel with a center weight of `weight`
@@ -152,8 +233,71 @@ pub fn weighted_avg_filter(input: &Image<f64>, size: u32, weight: u32) -> ImgPro
 
 /// Applies a Gaussian blur using a `size x size` kernel
 pub fn gaussian_blur(input: &Image<f64>, size: u32, sigma: f64) -> ImgProcResult<Image<f64>> {
-    let kernel = util::generate_gaussian_kernel(size, sigma)?;
-    Ok(linear_filter(input, &kernel)?)
+    gaussian_blur_padded(input, size, sigma, Padding::Clamp)
+}
+
+/// Applies a Gaussian blur using a `size x size` kernel. If `input` has an alpha channel, the
+/// color channels are premultiplied by alpha while filtering, so that the colors of transparent
+/// pixels do not bleed into their neighbors
+pub fn gaussian_blur_premultiplied(input: &Image<f64>, size: u32, sigma: f64) -> ImgProcResult<Image<f64>> {
+    GaussianKernel::new(size, sigma)?.apply_premultiplied(input)
+}
+
+/// Applies a Gaussian blur using a `size x size` kernel, handling pixels beyond the edges of
+/// `input` according to `padding`
+///
+/// # Arguments
+///
+/// * `input` - a reference to an `Image`
+/// * `size` - the side length of the kernel; must be odd
+/// * `sigma` - the standard deviation of the Gaussian; must be positive
+/// * `padding` - the edge handling to use; `Padding::Reflect` avoids bias at the borders
+pub fn gaussian_blur_padded(input: &Image<f64>, size: u32, sigma: f64, padding: Padding) -> ImgProcResult<Image<f64>> {
+    GaussianKernel::new(size, sigma)?.apply_padded(input, padding)
+}
+
+/// A precomputed `size x size` Gaussian kernel, which can be reused to apply the same
+/// Gaussian blur to multiple images
+#[derive(Debug, Clone, PartialEq)]
+pub struct GaussianKernel {
+    kernel: Vec<f64>,
+    separated: Option<(Vec<f64>, Vec<f64>)>,
+}
+
+impl GaussianKernel {
+    /// Creates a new `GaussianKernel` of size `size x size` with standard deviation `sigma`
+    pub fn new(size: u32, sigma: f64) -> ImgProcResult<Self> {
+        let kernel = util::generate_gaussian_kernel(size, sigma)?;
+        let separated = util::separate_kernel(&kernel)?;
+
+        Ok(GaussianKernel { kernel, separated })
+    }
+
+    /// Returns the 2D kernel
+    pub fn kernel(&self) -> &[f64] {
+        &self.kernel
+    }
+
+    /// Applies a Gaussian blur to `input`
+    pub fn apply(&self, input: &Image<f64>) -> ImgProcResult<Image<f64>> {
+        self.apply_padded(input, Padding::Clamp)
+    }
+
+    /// Applies a Gaussian blur to `input`, handling pixels beyond the edges of `input` according to
+    /// `padding`
+    pub fn apply_padded(&self, input: &Image<f64>, padding: Padding) -> ImgProcResult<Image<f64>> {
+        match &self.separated {
+            Some((vert, horz)) => separable_filter_padded(input, vert, horz, padding),
+            None if padding == Padding::Clamp => unseparable_filter(input, &self.kernel),
+            None => Err(ImgProcError::InvalidArgError("padding requires a separable kernel".to_string())),
+        }
+    }
+
+    /// Applies a Gaussian blur to `input`. If `input` has an alpha channel, the color channels are
+    /// premultiplied by alpha while filtering
+    pub fn apply_premultiplied(&self, input: &Image<f64>) -> ImgProcResult<Image<f64>> {
+        util::apply_premultiplied(input, |img| self.apply(img))
+    }
 }
 
 ////////////////
@@ -250,4 +394,227 @@ pub fn residual<T: Number>(original: &Image<T>, filtered: &Image<T>) -> ImgProcR
     }
 
     Ok(Image::from_slice(width, height, channels, alpha, &data))
-}
\ No newline at end of file
+}
+
+/// Applies the filter `f` to the region of `input` covered by `roi` and returns a copy of `input`
+/// with that region replaced by the result. Up to 16 pixels of the surrounding image are passed to
+/// `f` along with the region, so filters with a radius of up to 16 behave as if they were applied
+/// to the whole image. Pixels outside of `roi` are left unchanged
+pub fn apply_in_region<F>(input: &Image<u8>, roi: Rect, f: F) -> ImgProcResult<Image<u8>>
+    where F: Fn(&Image<u8>) -> ImgProcResult<Image<u8>> {
+    let (width, height) = input.info().wh();
+    if roi.is_empty() || roi.clip_to(width, height) != Some(roi) {
+        return Err(ImgProcError::InvalidArgError(format!("invalid roi: image is {}x{}, but roi \
+            spans ({}, {}) to ({}, {})", width, height, roi.x, roi.y,
+            roi.x as u64 + roi.width as u64, roi.y as u64 + roi.height as u64)));
+    }
+
+    let x = roi.x.saturating_sub(REGION_MARGIN);
+    let y = roi.y.saturating_sub(REGION_MARGIN);
+    let context = Rect::new(x, y, roi.x - x + roi.width + REGION_MARGIN, roi.y - y + roi.height + REGION_MARGIN)
+        .clip_to(width, height)
+        .unwrap();
+
+    let sub = input.subimage_rect(context)?;
+    let cropped = Image::from_vec(context.width, context.height, input.info().channels,
+                                  input.info().alpha, sub.to_vec());
+    let filtered = f(&cropped)?;
+    error::check_equal(cropped.info(), filtered.info(), "filtered region dimensions")?;
+
+    let mut output = input.clone();
+    for j in roi.y..(roi.y + roi.height) {
+        for i in roi.x..(roi.x + roi.width) {
+            output.set_pixel(i, j, filtered.get_pixel(i - context.x, j - context.y));
+        }
+    }
+
+    Ok(output)
+}
+
+/// Replaces each `block x block` region of `input` with its average color, giving a mosaic
+/// effect. Regions on the right and bottom edges of the image may be smaller. All channels,
+/// including alpha, are averaged
+///
+/// # Arguments
+///
+/// * `block` - Must be positive
+pub fn pixelate(input: &Image<u8>, block: u32) -> ImgProcResult<Image<u8>> {
+    error::check_positive(block, "block")?;
+
+    let channels = input.info().channels as usize;
+    let mut output = input.clone();
+    let mut sums = vec![0u64; channels];
+
+    for (x, y, tile) in input.tiles(block, block) {
+        sums.iter_mut().for_each(|sum| *sum = 0);
+        for i in 0..(tile.info().size() as usize) {
+            for (sum, val) in sums.iter_mut().zip(tile[i].iter()) {
+                *sum += *val as u64;
+            }
+        }
+
+        let size = tile.info().size() as f64;
+        let p_out: Vec<u8> = sums.iter().map(|sum| (*sum as f64 / size).round() as u8).collect();
+        let (width, height) = tile.info().wh();
+        for j in y..(y + height) {
+            for i in x..(x + width) {
+                output.set_pixel(i, j, &p_out);
+            }
+        }
+    }
+
+    Ok(output)
+}
+
+/// Divides `input` into `num_cells` Voronoi cells and colors each cell with the average color of
+/// its pixels, giving a crystallized effect. The cell centers are placed at pseudo-random pixel
+/// locations determined by `seed`, so the same `seed` always gives the same result, and each pixel
+/// belongs to the cell with the nearest center. All channels, including alpha, are averaged
+///
+/// # Arguments
+///
+/// * `num_cells` - Must be positive
+pub fn crystallize(input: &Image<u8>, num_cells: usize, seed: u64) -> ImgProcResult<Image<u8>> {
+    error::check_non_empty(input)?;
+    error::check_positive(num_cells, "num_cells")?;
+
+    let (width, height, channels) = input.info().whc();
+    let mut state = seed;
+    let centers: Vec<(i64, i64)> = (0..num_cells)
+        .map(|_| ((splitmix64(&mut state) % width as u64) as i64, (splitmix64(&mut state) % height as u64) as i64))
+        .collect();
+
+    // Assign each pixel to its nearest center, accumulating the color of each cell
+    let mut cells = Vec::with_capacity(input.info().size() as usize);
+    let mut sums = vec![vec![0u64; channels as usize]; num_cells];
+    let mut counts = vec![0u64; num_cells];
+    for y in 0..(height as i64) {
+        for x in 0..(width as i64) {
+            let cell = centers.iter()
+                .enumerate()
+                .min_by_key(|(_, (c_x, c_y))| (x - c_x) * (x - c_x) + (y - c_y) * (y - c_y))
+                .unwrap()
+                .0;
+
+            for (sum, val) in sums[cell].iter_mut().zip(input.get_pixel_unchecked(x as u32, y as u32).iter()) {
+                *sum += *val as u64;
+            }
+            counts[cell] += 1;
+            cells.push(cell);
+        }
+    }
+
+    let colors: Vec<Vec<u8>> = sums.iter()
+        .zip(counts.iter())
+        .map(|(sum, count)| sum.iter().map(|val| (*val as f64 / (*count).max(1) as f64).round() as u8).collect())
+        .collect();
+
+    let mut output = input.clone();
+    for (i, cell) in cells.iter().enumerate() {
+        output.set_pixel_indexed(i, &colors[*cell]);
+    }
+
+    Ok(output)
+}
+
+/// Applies an oil painting effect. The intensities (the mean of the color channels) of the pixels
+/// in the `(2 * radius + 1) x (2 * radius + 1)` neighborhood of each pixel are sorted into
+/// `intensity_levels` equally sized bins, and the output pixel is the average color of the pixels
+/// in the most populous bin. Ties go to the darker bin. Pixels outside the image are treated as
+/// copies of the nearest edge pixel, and all channels, including alpha, are averaged
+///
+/// # Arguments
+///
+/// * `radius` - Must be positive
+/// * `intensity_levels` - Must be between 1 and 256 (inclusive)
+pub fn oil_paint(input: &Image<u8>, radius: u32, intensity_levels: u32) -> ImgProcResult<Image<u8>> {
+    error::check_non_empty(input)?;
+    error::check_positive(radius, "radius")?;
+    error::check_in_range(intensity_levels, 1, 256, "intensity_levels")?;
+
+    let (width, height, channels) = input.info().whc();
+    let channels_non_alpha = input.info().channels_non_alpha() as usize;
+    let levels = intensity_levels as usize;
+    let r = radius as i64;
+
+    let mut counts = vec![0u32; levels];
+    let mut sums = vec![vec![0u64; channels as usize]; levels];
+    let mut output = input.clone();
+    for y in 0..height {
+        for x in 0..width {
+            counts.iter_mut().for_each(|count| *count = 0);
+            sums.iter_mut().for_each(|sum| sum.iter_mut().for_each(|val| *val = 0));
+
+            for j in -r..=r {
+                for i in -r..=r {
+                    let x_clamp = (x as i64 + i).clamp(0, width as i64 - 1) as u32;
+                    let y_clamp = (y as i64 + j).clamp(0, height as i64 - 1) as u32;
+                    let p = input.get_pixel_unchecked(x_clamp, y_clamp);
+
+                    let intensity = p[..channels_non_alpha].iter().map(|val| *val as usize).sum::<usize>()
+                        / channels_non_alpha;
+                    let bin = intensity * levels / 256;
+                    counts[bin] += 1;
+                    for (sum, val) in sums[bin].iter_mut().zip(p.iter()) {
+                        *sum += *val as u64;
+                    }
+                }
+            }
+
+            // `max_by_key` returns the last maximum, so search in reverse to prefer darker bins
+            let (bin, count) = counts.iter()
+                .enumerate()
+                .rev()
+                .max_by_key(|(_, count)| **count)
+                .unwrap();
+            let p_out: Vec<u8> = sums[bin].iter().map(|sum| (*sum as f64 / *count as f64).round() as u8).collect();
+            output.set_pixel(x, y, &p_out);
+        }
+    }
+
+    Ok(output)
+}
+
+/// Applies a cartoon effect. Each color channel is posterized to `color_levels` evenly spaced
+/// values, and pixels where the Sobel gradient magnitude of the grayscale image exceeds
+/// `edge_threshold` are drawn in black. Alpha channels are left unchanged
+///
+/// # Arguments
+///
+/// * `edge_threshold` - Must be non-negative
+/// * `color_levels` - Must be at least 2
+pub fn cartoonify(input: &Image<u8>, edge_threshold: f64, color_levels: u8) -> ImgProcResult<Image<u8>> {
+    error::check_non_empty(input)?;
+    error::check_non_neg(edge_threshold, "edge_threshold")?;
+    error::check_in_range(color_levels, 2, 255, "color_levels")?;
+
+    // Map each value to the center of its bin, spread over the full range
+    let levels = color_levels as u32;
+    let mut lookup_table: [u8; 256] = [0; 256];
+    util::generate_lookup_table(&mut lookup_table, |i| {
+        ((i as u32 * levels / 256) as f64 * 255.0 / (levels - 1) as f64).round() as u8
+    });
+
+    let gray: Image<f64> = analysis::to_grayscale(input).into();
+    let edges = edge::sobel(&gray)?;
+
+    let mut output = input.map_channels_if_alpha(|channel| lookup_table[channel as usize], |a| a);
+    let channels_non_alpha = input.info().channels_non_alpha() as usize;
+    for i in 0..(output.info().size() as usize) {
+        if edges[i][0] > edge_threshold {
+            output[i][..channels_non_alpha].iter_mut().for_each(|val| *val = 0);
+        }
+    }
+
+    Ok(output)
+}
+
+// Advances `state` and returns the next value of the SplitMix64 pseudo-random number generator
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e3779b97f4a7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+
+    z ^ (z >> 31)
+}
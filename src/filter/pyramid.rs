@@ -0,0 +1,320 @@
+use crate::error;
+use crate::error::{ImgProcResult, ImgProcError};
+use crate::image::{BaseImage, Image, ImageInfo};
+
+// The 5-tap binomial kernel from Burt and Adelson's "The Laplacian Pyramid as a Compact Image Code"
+const PYRAMID_KERNEL: [f64; 5] = [0.0625, 0.25, 0.375, 0.25, 0.0625];
+
+/// Builds a Gaussian pyramid with `levels` levels, where level 0 is `input` and each following
+/// level is the previous level blurred and halved in both dimensions (rounding up)
+pub fn gaussian_pyramid(input: &Image<f64>, levels: u32) -> ImgProcResult<Vec<Image<f64>>> {
+    check_levels(input, levels)?;
+
+    let mut pyramid = Vec::with_capacity(levels as usize);
+    pyramid.push(input.clone());
+
+    for i in 1..(levels as usize) {
+        let next = pyr_down(&pyramid[i - 1]);
+        pyramid.push(next);
+    }
+
+    Ok(pyramid)
+}
+
+/// Builds a Laplacian pyramid with `levels` levels. The first `levels - 1` levels hold the
+/// differences between consecutive levels of the Gaussian pyramid, and the last level holds the
+/// smallest level of the Gaussian pyramid. `input` can be recovered using
+/// [`collapse_laplacian_pyramid`](fn.collapse_laplacian_pyramid.html)
+pub fn laplacian_pyramid(input: &Image<f64>, levels: u32) -> ImgProcResult<Vec<Image<f64>>> {
+    let mut pyramid = gaussian_pyramid(input, levels)?;
+
+    for i in 0..(pyramid.len() - 1) {
+        let (width, height) = pyramid[i].info().wh();
+        let expanded = pyr_up(&pyramid[i + 1], width, height);
+
+        for (val, exp) in pyramid[i].data_mut().iter_mut().zip(expanded.data().iter()) {
+            *val -= exp;
+        }
+    }
+
+    Ok(pyramid)
+}
+
+/// Reconstructs an image from a Laplacian pyramid produced by
+/// [`laplacian_pyramid`](fn.laplacian_pyramid.html)
+pub fn collapse_laplacian_pyramid(pyramid: &[Image<f64>]) -> ImgProcResult<Image<f64>> {
+    let mut output = pyramid.last()
+        .ok_or_else(|| ImgProcError::InvalidArgError("pyramid must not be empty".to_string()))?
+        .clone();
+
+    for level in pyramid.iter().rev().skip(1) {
+        let (width, height) = level.info().wh();
+        error::check_equal(level.info().channels, output.info().channels, "pyramid channels")?;
+        error::check_equal((width.div_ceil(2), height.div_ceil(2)), output.info().wh(), "pyramid level dimensions")?;
+
+        output = pyr_up(&output, width, height);
+        for (val, lap) in output.data_mut().iter_mut().zip(level.data().iter()) {
+            *val += lap;
+        }
+    }
+
+    Ok(output)
+}
+
+/// Blends `a` and `b` using Burt and Adelson's multi-band blending over `levels` pyramid levels.
+/// Each output pixel is taken from `a` where `mask` is 255 and from `b` where `mask` is 0. `mask`
+/// must either be a single-channel image or have the same number of channels as `a` and `b`
+pub fn blend_pyramid(a: &Image<u8>, b: &Image<u8>, mask: &Image<u8>, levels: u32) -> ImgProcResult<Image<u8>> {
+    error::check_equal(a.info().whc(), b.info().whc(), "image dimensions")?;
+    error::check_equal(a.info().wh(), mask.info().wh(), "image and mask dimensions")?;
+    if mask.info().channels != 1 && mask.info().channels != a.info().channels {
+        return Err(ImgProcError::InvalidArgError("mask must have either 1 channel or \
+            the same number of channels as the input images".to_string()));
+    }
+
+    let lap_a = laplacian_pyramid(&a.clone().into(), levels)?;
+    let lap_b = laplacian_pyramid(&b.clone().into(), levels)?;
+    let gauss_mask = gaussian_pyramid(&mask.map_channels(|channel| channel as f64 / 255.0), levels)?;
+
+    let channels = a.info().channels as usize;
+    let mut p_out = Vec::with_capacity(channels);
+    let mut blended = Vec::with_capacity(levels as usize);
+
+    for ((level_a, level_b), level_mask) in lap_a.iter().zip(lap_b.iter()).zip(gauss_mask.iter()) {
+        let (width, height) = level_a.info().wh();
+        let mut level = Image::blank(level_a.info());
+
+        for y in 0..height {
+            for x in 0..width {
+                let p_a = level_a.get_pixel_unchecked(x, y);
+                let p_b = level_b.get_pixel_unchecked(x, y);
+                let p_mask = level_mask.get_pixel_unchecked(x, y);
+
+                p_out.clear();
+                for c in 0..channels {
+                    // A single-channel mask is applied to every channel
+                    let weight = p_mask[c.min(p_mask.len() - 1)];
+                    p_out.push(weight * p_a[c] + (1.0 - weight) * p_b[c]);
+                }
+
+                level.set_pixel(x, y, &p_out);
+            }
+        }
+
+        blended.push(level);
+    }
+
+    Ok(collapse_laplacian_pyramid(&blended)?.into())
+}
+
+/// The exponents applied to each quality measure when computing the per-pixel weights of
+/// [`exposure_fuse`](fn.exposure_fuse.html). An exponent of 0 ignores that measure
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FusionWeights {
+    /// Exponent of the absolute value of the Laplacian of the grayscale image, which favors
+    /// detailed regions
+    pub contrast: f64,
+
+    /// Exponent of the standard deviation of the color channels, which favors vivid colors. Has no
+    /// effect on grayscale images
+    pub saturation: f64,
+
+    /// Exponent of a Gaussian curve around mid-gray, which favors pixels that are neither under-
+    /// nor over-exposed
+    pub well_exposedness: f64,
+}
+
+impl Default for FusionWeights {
+    fn default() -> Self {
+        FusionWeights { contrast: 1.0, saturation: 1.0, well_exposedness: 1.0 }
+    }
+}
+
+/// Fuses differently exposed frames of the same scene into one well-exposed image using Mertens'
+/// exposure fusion. Each pixel of each frame is weighted by its contrast, saturation and
+/// well-exposedness (see [`FusionWeights`](struct.FusionWeights.html)), and the frames are
+/// blended with these weights using multi-band blending. All frames must have the same dimensions
+/// and channels
+pub fn exposure_fuse(frames: &[Image<u8>], weights: FusionWeights) -> ImgProcResult<Image<u8>> {
+    error::check_non_empty_slice(frames, "frames")?;
+
+    let first = &frames[0];
+    error::check_non_empty(first)?;
+    for frame in frames.iter().skip(1) {
+        error::check_equal(first.info(), frame.info(), "frame dimensions")?;
+    }
+
+    // Use as many levels as the frames can be halved into
+    let (mut width, mut height) = first.info().wh();
+    let mut levels = 1;
+    while width >= 2 && height >= 2 {
+        width = width.div_ceil(2);
+        height = height.div_ceil(2);
+        levels += 1;
+    }
+
+    // Normalize the weights so that they sum to 1 at each pixel
+    let mut weight_maps: Vec<Image<f64>> = frames.iter().map(|frame| fusion_weight(frame, &weights)).collect();
+    for i in 0..(first.info().size() as usize) {
+        let sum: f64 = weight_maps.iter().map(|map| map.data()[i]).sum();
+        weight_maps.iter_mut().for_each(|map| map.data_mut()[i] /= sum);
+    }
+
+    let mut blended: Option<Vec<Image<f64>>> = None;
+    for (frame, weight_map) in frames.iter().zip(weight_maps.iter()) {
+        let lap = laplacian_pyramid(&frame.clone().into(), levels)?;
+        let gauss = gaussian_pyramid(weight_map, levels)?;
+
+        let weighted: Vec<Image<f64>> = lap.iter()
+            .zip(gauss.iter())
+            .map(|(level, weight)| {
+                let mut level = level.clone();
+                let channels = level.info().channels as usize;
+                for (p, w) in level.data_mut().chunks_mut(channels).zip(weight.data().iter()) {
+                    p.iter_mut().for_each(|val| *val *= w);
+                }
+                level
+            })
+            .collect();
+
+        blended = Some(match blended {
+            None => weighted,
+            Some(mut sum) => {
+                for (level_sum, level) in sum.iter_mut().zip(weighted.iter()) {
+                    for (val, w) in level_sum.data_mut().iter_mut().zip(level.data().iter()) {
+                        *val += w;
+                    }
+                }
+                sum
+            },
+        });
+    }
+
+    Ok(collapse_laplacian_pyramid(&blended.unwrap())?.into())
+}
+
+fn check_levels(input: &Image<f64>, levels: u32) -> ImgProcResult<()> {
+    error::check_non_empty(input)?;
+
+    error::check_positive(levels, "levels")?;
+
+    let (mut width, mut height) = input.info().wh();
+    for _ in 1..levels {
+        if width < 2 || height < 2 {
+            return Err(ImgProcError::InvalidArgError(format!("invalid levels: image is {}x{}, \
+                which cannot be halved {} times", input.info().width, input.info().height, levels - 1)));
+        }
+
+        width = width.div_ceil(2);
+        height = height.div_ceil(2);
+    }
+
+    Ok(())
+}
+
+// Blurs and halves `input`
+fn pyr_down(input: &Image<f64>) -> Image<f64> {
+    let (width, height) = input.info().wh();
+    let horz = pyr_pass(input, width.div_ceil(2), false, false);
+    pyr_pass(&horz, height.div_ceil(2), true, false)
+}
+
+// Doubles and blurs `input` to an image of size `width x height`
+fn pyr_up(input: &Image<f64>, width: u32, height: u32) -> Image<f64> {
+    let horz = pyr_pass(input, width, false, true);
+    pyr_pass(&horz, height, true, true)
+}
+
+// Applies `PYRAMID_KERNEL` along one axis while either halving (`up == false`) or doubling
+// (`up == true`) the length of that axis. Pixels outside the image are clamped to the edge
+fn pyr_pass(input: &Image<f64>, len_out: u32, is_vert: bool, up: bool) -> Image<f64> {
+    let (width_in, height_in, channels, alpha) = input.info().whca();
+    let (width, height, len_in) = if is_vert {
+        (width_in, len_out, height_in as i32)
+    } else {
+        (len_out, height_in, width_in as i32)
+    };
+
+    let mut output = Image::blank(ImageInfo::new(width, height, channels, alpha));
+    let mut p_out = vec![0.0; channels as usize];
+
+    for y in 0..height {
+        for x in 0..width {
+            let i = if is_vert { y as i32 } else { x as i32 };
+            p_out.iter_mut().for_each(|val| *val = 0.0);
+
+            for m in -2..=2 {
+                let (src, weight) = if up {
+                    // Only even positions of the upsampled axis hold input pixels
+                    if (i - m) % 2 != 0 {
+                        continue;
+                    }
+                    ((i - m) / 2, 2.0 * PYRAMID_KERNEL[(m + 2) as usize])
+                } else {
+                    (2 * i + m, PYRAMID_KERNEL[(m + 2) as usize])
+                };
+
+                let src = src.clamp(0, len_in - 1) as u32;
+                let p_in = if is_vert {
+                    input.get_pixel_unchecked(x, src)
+                } else {
+                    input.get_pixel_unchecked(src, y)
+                };
+
+                for (val, p) in p_out.iter_mut().zip(p_in.iter()) {
+                    *val += weight * p;
+                }
+            }
+
+            output.set_pixel(x, y, &p_out);
+        }
+    }
+
+    output
+}
+
+// Computes the unnormalized exposure fusion weight of each pixel of `input`
+fn fusion_weight(input: &Image<u8>, weights: &FusionWeights) -> Image<f64> {
+    // Small enough not to matter, but keeps the weights from all being zero
+    const EPSILON: f64 = 1e-12;
+
+    let (width, height) = input.info().wh();
+    let channels = input.info().channels_non_alpha() as usize;
+    let gray = input.map_pixels(|p, p_out: &mut Vec<f64>| {
+        p_out.push(p[..channels].iter().map(|val| *val as f64 / 255.0).sum::<f64>() / channels as f64);
+    });
+    let gray_at = |x: i64, y: i64| {
+        gray.get_pixel_unchecked(x.clamp(0, width as i64 - 1) as u32, y.clamp(0, height as i64 - 1) as u32)[0]
+    };
+
+    let mut output = Image::blank(ImageInfo::new(width, height, 1, false));
+    for y in 0..height {
+        for x in 0..width {
+            let p = input.get_pixel_unchecked(x, y);
+            let (i, j) = (x as i64, y as i64);
+
+            let contrast = (gray_at(i - 1, j) + gray_at(i + 1, j) + gray_at(i, j - 1) + gray_at(i, j + 1)
+                - 4.0 * gray_at(i, j)).abs();
+
+            let saturation = if channels >= 3 {
+                let mean = gray_at(i, j);
+                (p[..channels].iter()
+                    .map(|val| (*val as f64 / 255.0 - mean).powi(2))
+                    .sum::<f64>() / channels as f64).sqrt()
+            } else {
+                1.0
+            };
+
+            let well_exposedness = p[..channels].iter()
+                .map(|val| (-(*val as f64 / 255.0 - 0.5).powi(2) / 0.08).exp())
+                .product::<f64>();
+
+            output.set_pixel(x, y, &[contrast.powf(weights.contrast)
+                * saturation.powf(weights.saturation)
+                * well_exposedness.powf(weights.well_exposedness)
+                + EPSILON]);
+        }
+    }
+
+    output
+}
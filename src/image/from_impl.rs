@@ -1,3 +1,4 @@
+use crate::util;
 use crate::image::Image;
 
 impl From<Image<u8>> for Image<f64> {
@@ -42,14 +43,18 @@ impl From<Image<f32>> for Image<f64> {
     }
 }
 
+/// Rounds each channel, clamping it to the range 0 to 255 (including infinities). NaN channels
+/// become 0
 impl From<Image<f64>> for Image<u8> {
     fn from(img: Image<f64>) -> Image<u8> {
-        img.map_channels(|channel| channel.round() as u8)
+        img.map_channels(util::round_to_u8)
     }
 }
 
+/// Rounds each channel, clamping it to the range 0 to 255 (including infinities). NaN channels
+/// become 0
 impl From<Image<f32>> for Image<u8> {
     fn from(img: Image<f32>) -> Image<u8> {
-        img.map_channels(|channel| channel.round() as u8)
+        img.map_channels(|channel| util::round_to_u8(channel as f64))
     }
 }
\ No newline at end of file
@@ -59,13 +59,21 @@ pub use self::sub_image::*;
 pub use self::pixel::*;
 pub use self::from_impl::*;
 pub use self::pixel_iter::*;
+pub use self::tile_iter::*;
+pub use self::rect::*;
 
 mod sub_image;
 mod pixel;
 mod from_impl;
 mod pixel_iter;
+mod tile_iter;
+mod rect;
 
 use crate::error;
+use crate::error::{ImgProcResult, ImgProcError};
+
+// Characters used by `to_ascii_art`, from darkest to lightest
+const ASCII_RAMP: &[u8] = b"@%#*+=-:. ";
 
 /// A struct representing an image
 #[derive(Debug, Clone, PartialEq)]
@@ -227,6 +235,18 @@ impl<T: Number> Image<T> {
         }
     }
 
+    /// Creates an `Image<T>` with every pixel set to `color`. The number of channels is the length
+    /// of `color`, which must be between 1 and 4; images with 2 or 4 channels have an alpha
+    /// channel
+    pub fn from_color(width: u32, height: u32, color: &[T]) -> ImgProcResult<Self> {
+        let (channels, alpha) = channels_from_len(color.len())?;
+
+        Ok(Image {
+            info: ImageInfo{ width, height, channels, alpha },
+            data: color.repeat((width * height) as usize),
+        })
+    }
+
     /// Creates an `Image<T>` populated with zeroes
     pub fn blank(info: ImageInfo) -> Self {
         Image {
@@ -302,6 +322,43 @@ impl<T: Number> Image<T> {
         SubImage::new(width, height, self.info.channels, self.info.alpha, data)
     }
 
+    /// Returns a `SubImage<T>` borrowing the part of the image of width `width` and height
+    /// `height`, with upper left hand corner located at `(x, y)`. Pixels are stored in row-major
+    /// order and are not copied
+    pub fn subimage(&self, x: u32, y: u32, width: u32, height: u32) -> ImgProcResult<SubImage<'_, T>> {
+        if x as u64 + width as u64 > self.info.width as u64 || y as u64 + height as u64 > self.info.height as u64 {
+            return Err(ImgProcError::InvalidArgError(format!("invalid subimage: image is {}x{}, \
+                but subimage spans ({}, {}) to ({}, {})", self.info.width, self.info.height,
+                x, y, x as u64 + width as u64, y as u64 + height as u64)));
+        }
+
+        let mut data = Vec::with_capacity((width * height) as usize);
+
+        for j in y..(y + height) {
+            for i in x..(x + width) {
+                data.push(self.get_pixel_unchecked(i, j));
+            }
+        }
+
+        Ok(SubImage::new(width, height, self.info.channels, self.info.alpha, data))
+    }
+
+    /// Returns a `SubImage<T>` representing the region of the image covered by `rect`. Returns an
+    /// error if `rect` extends past the edge of the image
+    pub fn subimage_rect(&self, rect: Rect) -> ImgProcResult<SubImage<'_, T>> {
+        self.subimage(rect.x, rect.y, rect.width, rect.height)
+    }
+
+    /// Returns an iterator over non-overlapping tiles of size `tile_width x tile_height`. Tiles on
+    /// the right and bottom edges of the image may be smaller
+    ///
+    /// # Panics
+    ///
+    /// Panics if `tile_width` or `tile_height` is zero
+    pub fn tiles(&self, tile_width: u32, tile_height: u32) -> TileIter<'_, T> {
+        TileIter::new(self, tile_width, tile_height)
+    }
+
     /// Returns a `SubImage<T>` representing the row or column of pixels of length `size` centered at
     /// `(x, y)`. If `is_vert` is `true`, returns the column; otherwise, returns the row.
     /// Uses clamp padding for edge pixels (edge pixels are repeated indefinitely)
@@ -339,6 +396,40 @@ impl<T: Number> Image<T> {
         }
     }
 
+    /// Returns a `SubImage<T>` representing the row or column of pixels of length `size` centered at
+    /// `(x, y)`. If `is_vert` is `true`, returns the column; otherwise, returns the row.
+    /// Uses reflect padding for edge pixels (pixels are mirrored about the edge pixel, which is
+    /// not repeated)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` or `y` is out of bounds
+    pub fn get_neighborhood_1d_reflect(&self, x: u32, y: u32, size: u32, is_vert: bool) -> SubImage<'_, T> {
+        error::check_xy(x, y, self.info.width, self.info.height);
+
+        let mut data = Vec::new();
+
+        if is_vert {
+            let start_y = (y as i32) - (size as i32) / 2;
+
+            for i in 0..size {
+                let curr_y = reflect_coord(start_y + (i as i32), self.info.height);
+                data.push(self.get_pixel(x, curr_y));
+            }
+
+            SubImage::new(1, size, self.info.channels, self.info.alpha, data)
+        } else {
+            let start_x = (x as i32) - (size as i32) / 2;
+
+            for i in 0..size {
+                let curr_x = reflect_coord(start_x + (i as i32), self.info.width);
+                data.push(self.get_pixel(curr_x, y));
+            }
+
+            SubImage::new(size, 1, self.info.channels, self.info.alpha, data)
+        }
+    }
+
     /// Returns a `SubImage<T>` representing the "square" of pixels of side length `size` centered
     /// at `(x, y)`. Uses clamp padding for edge pixels (edge pixels are repeated indefinitely)
     ///
@@ -381,6 +472,23 @@ impl<T: Number> Image<T> {
         self.data[start..((self.info.channels as usize) + start)].clone_from_slice(pixel);
     }
 
+    /// Replaces the pixel located at `(x, y)` with `pixel`, returning an error instead of panicking if
+    /// the length of `pixel` is not equal to the number of channels in the image or `x` or `y` is
+    /// out of bounds
+    pub fn set_pixel_checked(&mut self, x: u32, y: u32, pixel: &[T]) -> ImgProcResult<()> {
+        if pixel.len() != self.info.channels as usize {
+            return Err(ImgProcError::InvalidArgError(format!("invalid pixel length: the number of \
+                channels is {}, but the pixel length is {}", self.info.channels, pixel.len())));
+        }
+        if x >= self.info.width || y >= self.info.height {
+            return Err(ImgProcError::InvalidArgError(format!("invalid coordinates: the image is {}x{}, \
+                but the coordinates are ({}, {})", self.info.width, self.info.height, x, y)));
+        }
+
+        self.set_pixel(x, y, pixel);
+        Ok(())
+    }
+
     /// Replaces the pixel at index `index` with `pixel`
     ///
     /// # Panics
@@ -393,6 +501,25 @@ impl<T: Number> Image<T> {
         self.data[start..((self.info.channels as usize) + start)].clone_from_slice(pixel);
     }
 
+    /// Sets every pixel to `color`, returning an error if the length of `color` is not equal to
+    /// the number of channels in the image
+    pub fn fill(&mut self, color: &[T]) -> ImgProcResult<()> {
+        error::check_equal(self.info.channels as usize, color.len(), "color length")?;
+
+        for pixel in self.data.chunks_exact_mut(color.len()) {
+            pixel.copy_from_slice(color);
+        }
+
+        Ok(())
+    }
+
+    /// Sets every channel of every pixel to zero
+    pub fn clear(&mut self) {
+        for val in self.data.iter_mut() {
+            *val = 0.into();
+        }
+    }
+
     /// Applies function `f` to each pixel
     pub fn map_pixels<S: Number, F>(&self, f: F) -> Image<S>
         where F: Fn(&[T], &mut Vec<S>) {
@@ -485,6 +612,127 @@ impl<T: Number> Image<T> {
         }
     }
 
+    /// Applies function `f` to each pair of corresponding pixels in `self` and `other`
+    pub fn zip_map<S: Number, F>(&self, other: &Image<T>, f: F) -> ImgProcResult<Image<S>>
+        where F: Fn(&[T], &[T], &mut Vec<S>) {
+        error::check_equal(self.info.whc(), other.info.whc(), "image dimensions")?;
+
+        let mut data = Vec::with_capacity(self.info.size() as usize);
+        let mut p_out = Vec::new();
+
+        for i in 0..(self.info.size() as usize) {
+            f(&self[i], &other[i], &mut p_out);
+            data.append(&mut p_out);
+        }
+
+        // `f` is never called on an empty image, so the channels are kept unchanged
+        let channels = if self.info.size() == 0 {
+            self.info.channels
+        } else {
+            (data.len() as u32 / self.info.size()) as u8
+        };
+
+        Ok(Image {
+            info: ImageInfo {
+                width: self.info.width,
+                height: self.info.height,
+                channels,
+                alpha: self.info.alpha
+            },
+            data,
+        })
+    }
+
+    /// Applies function `f` to the `(2 * radius + 1) x (2 * radius + 1)` neighborhood of each
+    /// pixel and returns an `Image<T>` with the resulting pixels. The neighborhood is passed to `f`
+    /// row by row, and pixels outside of the image are clamped to the nearest edge pixel. The
+    /// number of channels of the output image is the length of the pixels returned by `f`
+    pub fn map_neighborhood<F>(&self, radius: u32, f: F) -> Image<T>
+        where F: Fn(&[&[T]]) -> Vec<T> {
+        let (width, height) = self.info.wh();
+        let r = radius as i64;
+        let mut data = Vec::with_capacity(self.info.full_size() as usize);
+        let mut neighborhood = Vec::with_capacity(((2 * r + 1) * (2 * r + 1)) as usize);
+
+        for y in 0..height {
+            for x in 0..width {
+                neighborhood.clear();
+
+                for j in (y as i64 - r)..=(y as i64 + r) {
+                    for i in (x as i64 - r)..=(x as i64 + r) {
+                        let i_clamp = i.clamp(0, width as i64 - 1) as u32;
+                        let j_clamp = j.clamp(0, height as i64 - 1) as u32;
+                        neighborhood.push(self.get_pixel_unchecked(i_clamp, j_clamp));
+                    }
+                }
+
+                data.extend(f(&neighborhood));
+            }
+        }
+
+        // `f` is never called on an empty image, so the channels are kept unchanged
+        let channels = if self.info.size() == 0 {
+            self.info.channels
+        } else {
+            (data.len() as u32 / self.info.size()) as u8
+        };
+
+        Image {
+            info: ImageInfo {
+                width,
+                height,
+                channels,
+                alpha: self.info.alpha
+            },
+            data,
+        }
+    }
+
+    /// Applies function `f` to each pixel along with its `(x, y)` coordinates and returns an
+    /// `Image<T>` with the resulting pixels. The number of channels of the output image is the
+    /// length of the pixels returned by `f`
+    pub fn map_with_coords<F>(&self, f: F) -> Image<T>
+        where F: Fn(u32, u32, &[T]) -> Vec<T> {
+        let (width, height) = self.info.wh();
+        let mut data = Vec::with_capacity(self.info.full_size() as usize);
+
+        for y in 0..height {
+            for x in 0..width {
+                data.extend(f(x, y, self.get_pixel_unchecked(x, y)));
+            }
+        }
+
+        // `f` is never called on an empty image, so the channels are kept unchanged
+        let channels = if self.info.size() == 0 {
+            self.info.channels
+        } else {
+            (data.len() as u32 / self.info.size()) as u8
+        };
+
+        Image {
+            info: ImageInfo {
+                width,
+                height,
+                channels,
+                alpha: self.info.alpha
+            },
+            data,
+        }
+    }
+
+    /// Returns `true` if `self` and `other` have the same dimensions, channels and alpha, and
+    /// every channel of `self` is within `tolerance` of the corresponding channel of `other`
+    pub fn approx_eq(&self, other: &Image<T>, tolerance: T) -> bool {
+        if self.info != other.info {
+            return false;
+        }
+
+        self.data.iter().zip(other.data.iter()).all(|(a, b)| {
+            let diff = if a > b { *a - *b } else { *b - *a };
+            diff <= tolerance
+        })
+    }
+
     /// Applies function `f` to each pixel
     pub fn apply_pixels<F>(&mut self, f: F)
         where F: Fn(&[T], &mut Vec<T>) {
@@ -548,6 +796,200 @@ impl<T: Number> Image<T> {
             self.data[i] = f(self.data[i]);
         }
     }
+
+    /// Returns a copy of the image data in planar layout, where all values of channel 0 are stored
+    /// row by row, followed by all values of channel 1, and so on. Channel `c` of pixel `(x, y)` is
+    /// at index `(c * height + y) * width + x`
+    pub fn to_planar(&self) -> Vec<T> {
+        let channels = self.info.channels as usize;
+        let mut data = Vec::with_capacity(self.data.len());
+
+        for c in 0..channels {
+            data.extend(self.data.iter().skip(c).step_by(channels));
+        }
+
+        data
+    }
+
+    /// Creates an `Image<T>` from `data` in the planar layout described in
+    /// [`to_planar`](#method.to_planar). The length of `data` must be exactly
+    /// `width * height * channels`
+    pub fn from_planar(width: u32, height: u32, channels: u8, alpha: bool, data: &[T]) -> ImgProcResult<Self> {
+        let size = width as usize * height as usize;
+        let len = size * channels as usize;
+        if data.len() != len {
+            return Err(ImgProcError::InvalidArgError(format!("invalid buffer length: expected {} \
+                values for a {}x{} image with {} channels, but got {}", len, width, height, channels, data.len())));
+        }
+
+        let mut interleaved = Vec::with_capacity(len);
+        for i in 0..size {
+            interleaved.extend(data.iter().skip(i).step_by(size));
+        }
+
+        Ok(Image::from_vec(width, height, channels, alpha, interleaved))
+    }
+}
+
+impl Image<u8> {
+    /// Returns the raw channel buffer without copying. Pixels are stored row by row from the top
+    /// left corner (row-major), and the channels of each pixel are stored next to each other
+    /// (interleaved), so channel `c` of pixel `(x, y)` is at index
+    /// `(y * width + x) * channels + c`. The length of the buffer is `width * height * channels`
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Creates an `Image<u8>` by copying `bytes`, which must use the row-major, channel-interleaved
+    /// layout described in [`as_bytes`](#method.as_bytes) and have a length of exactly
+    /// `width * height * channels`
+    pub fn from_bytes_with_layout(width: u32, height: u32, channels: u8, alpha: bool, bytes: &[u8]) -> ImgProcResult<Self> {
+        let len = width as usize * height as usize * channels as usize;
+        if bytes.len() != len {
+            return Err(ImgProcError::InvalidArgError(format!("invalid buffer length: expected {} \
+                bytes for a {}x{} image with {} channels, but got {}", len, width, height, channels, bytes.len())));
+        }
+
+        Ok(Image::from_slice(width, height, channels, alpha, bytes))
+    }
+
+    /// Creates an `Image<u8>` from a buffer whose rows are `stride` bytes apart, dropping the
+    /// padding at the end of each row. `stride` must be at least `width * channels`, and `data`
+    /// must contain `height` rows (the padding after the last row may be omitted)
+    pub fn from_strided(width: u32, height: u32, channels: u8, alpha: bool, data: &[u8], stride: usize) -> ImgProcResult<Self> {
+        let row_len = width as usize * channels as usize;
+        if stride < row_len {
+            return Err(ImgProcError::InvalidArgError(format!("invalid stride: must be at least {}, \
+                but is {}", row_len, stride)));
+        }
+
+        let len = if height == 0 { 0 } else { stride * (height as usize - 1) + row_len };
+        if data.len() < len {
+            return Err(ImgProcError::InvalidArgError(format!("invalid buffer length: expected at \
+                least {} bytes, but got {}", len, data.len())));
+        }
+
+        let mut pixels = Vec::with_capacity(row_len * height as usize);
+        for y in 0..(height as usize) {
+            pixels.extend_from_slice(&data[(y * stride)..(y * stride + row_len)]);
+        }
+
+        Ok(Image::from_vec(width, height, channels, alpha, pixels))
+    }
+
+    /// Returns the image data as `f32` values in the planar layout described in
+    /// [`to_planar`](#method.to_planar), with each value of channel `c` scaled to the range [0, 1]
+    /// and then normalized to `(value - mean[c]) / std[c]`
+    ///
+    /// # Arguments
+    ///
+    /// * `mean` - Must have one value per channel
+    /// * `std` - Must have one non-zero value per channel
+    pub fn to_normalized_tensor(&self, mean: &[f32], std: &[f32]) -> ImgProcResult<Vec<f32>> {
+        error::check_equal(self.info.channels as usize, mean.len(), "mean length")?;
+        error::check_equal(self.info.channels as usize, std.len(), "std length")?;
+        if std.contains(&0.0) {
+            return Err(ImgProcError::InvalidArgError("std values must be non-zero".to_string()));
+        }
+
+        let size = self.info.size().max(1) as usize;
+        Ok(self.to_planar()
+            .iter()
+            .enumerate()
+            .map(|(i, val)| {
+                let c = i / size;
+                (*val as f32 / 255.0 - mean[c]) / std[c]
+            })
+            .collect())
+    }
+
+    /// Adds `v` to each non-alpha channel, clamping the result to the range 0 to 255
+    pub fn saturating_add_scalar(&self, v: i32) -> Image<u8> {
+        self.map_channels_if_alpha(|channel| (channel as i32).saturating_add(v).clamp(0, 255) as u8, |a| a)
+    }
+
+    /// Adds `v` to each non-alpha channel, wrapping the result modulo 256
+    pub fn wrapping_add_scalar(&self, v: i32) -> Image<u8> {
+        self.map_channels_if_alpha(|channel| (channel as i32).wrapping_add(v) as u8, |a| a)
+    }
+
+    /// Multiplies each non-alpha channel by `v`, clamping the result to the range 0 to 255
+    pub fn saturating_mul_scalar(&self, v: i32) -> Image<u8> {
+        self.map_channels_if_alpha(|channel| (channel as i32).saturating_mul(v).clamp(0, 255) as u8, |a| a)
+    }
+
+    /// Multiplies each non-alpha channel by `v`, wrapping the result modulo 256
+    pub fn wrapping_mul_scalar(&self, v: i32) -> Image<u8> {
+        self.map_channels_if_alpha(|channel| (channel as i32).wrapping_mul(v) as u8, |a| a)
+    }
+
+    /// Creates an `Image<u8>` that fades linearly from `start` at the left edge to `end` at the
+    /// right edge if `horizontal` is `true`, or from `start` at the top edge to `end` at the bottom
+    /// edge otherwise. `start` and `end` must have the same length, which determines the channels
+    /// as in [`from_color`](#method.from_color)
+    pub fn linear_gradient(width: u32, height: u32, start: &[u8], end: &[u8], horizontal: bool) -> ImgProcResult<Self> {
+        error::check_equal(start.len(), end.len(), "start and end lengths")?;
+        let (channels, alpha) = channels_from_len(start.len())?;
+
+        let len = if horizontal { width } else { height };
+        let steps: Vec<Vec<u8>> = (0..len)
+            .map(|i| {
+                let t = if len > 1 { i as f64 / (len - 1) as f64 } else { 0.0 };
+                start.iter()
+                    .zip(end.iter())
+                    .map(|(a, b)| (*a as f64 + t * (*b as f64 - *a as f64)).round() as u8)
+                    .collect()
+            })
+            .collect();
+
+        let mut data = Vec::with_capacity((width * height * channels as u32) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                data.extend_from_slice(&steps[if horizontal { x } else { y } as usize]);
+            }
+        }
+
+        Ok(Image::from_vec(width, height, channels, alpha, data))
+    }
+
+    /// Returns a text preview of the image with `width` columns (at most the image width), where
+    /// each character represents the mean brightness of the color channels in a block of pixels,
+    /// from `'@'` for black to `' '` for white. Each character covers a block about twice as tall
+    /// as it is wide to make up for the shape of terminal characters. Rows are separated by
+    /// newlines
+    pub fn to_ascii_art(&self, width: u32) -> String {
+        let (img_width, img_height) = self.info.wh();
+        if img_width == 0 || img_height == 0 {
+            return String::new();
+        }
+
+        let cols = width.clamp(1, img_width);
+        let rows = ((img_height as f64 * cols as f64 / img_width as f64 / 2.0).round() as u32).clamp(1, img_height);
+        let channels_non_alpha = self.info.channels_non_alpha() as usize;
+
+        let mut art = String::with_capacity(((cols + 1) * rows) as usize);
+        for j in 0..rows {
+            let (y_start, y_end) = (j * img_height / rows, (j + 1) * img_height / rows);
+            for i in 0..cols {
+                let (x_start, x_end) = (i * img_width / cols, (i + 1) * img_width / cols);
+
+                let mut sum = 0.0;
+                for y in y_start..y_end {
+                    for x in x_start..x_end {
+                        let p = self.get_pixel_unchecked(x, y);
+                        sum += p[..channels_non_alpha].iter().map(|val| *val as f64).sum::<f64>();
+                    }
+                }
+
+                let count = ((x_end - x_start) * (y_end - y_start)) as f64 * channels_non_alpha as f64;
+                let index = (sum / count / 255.0 * (ASCII_RAMP.len() - 1) as f64).round() as usize;
+                art.push(ASCII_RAMP[index] as char);
+            }
+            art.push('\n');
+        }
+
+        art
+    }
 }
 
 impl<T: Number> BaseImage<T> for Image<T> {
@@ -576,4 +1018,31 @@ impl<T: Number> std::ops::IndexMut<usize> for Image<T> {
         let start = i * (self.info.channels as usize);
         &mut self.data[start..(start + self.info.channels as usize)]
     }
-}
\ No newline at end of file
+}
+
+// Maps `coord` into the range `0..len` by mirroring it about the first and last indices, so that
+// `-i` becomes `i` and `len - 1 + i` becomes `len - 1 - i`
+fn reflect_coord(coord: i32, len: u32) -> u32 {
+    if len == 1 {
+        return 0;
+    }
+
+    let period = 2 * (len as i32 - 1);
+    let coord = coord.rem_euclid(period);
+
+    if coord < len as i32 {
+        coord as u32
+    } else {
+        (period - coord) as u32
+    }
+}
+
+// Returns the number of channels and whether there is an alpha channel for a pixel of length `len`
+fn channels_from_len(len: usize) -> ImgProcResult<(u8, bool)> {
+    if len == 0 || len > 4 {
+        return Err(ImgProcError::InvalidArgError(format!("invalid color: expected 1 to 4 \
+            channels, but got {}", len)));
+    }
+
+    Ok((len as u8, len % 2 == 0))
+}
@@ -0,0 +1,71 @@
+/// A struct representing an axis-aligned rectangle with upper left corner located at `(x, y)`
+///
+/// # Examples
+/// ```rust
+/// # fn main() {
+/// use imgproc_rs::image::Rect;
+///
+/// let a = Rect::new(0, 0, 4, 4);
+/// let b = Rect::new(2, 2, 4, 4);
+///
+/// // The overlapping region of two rectangles
+/// assert_eq!(Some(Rect::new(2, 2, 2, 2)), a.intersect(&b));
+///
+/// // The part of a rectangle that lies inside a 5x5 image
+/// assert_eq!(Some(Rect::new(2, 2, 3, 3)), b.clip_to(5, 5));
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Rect {
+    /// Creates a new `Rect`
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> Self {
+        Rect { x, y, width, height }
+    }
+
+    /// Returns `true` if the rectangle has zero width or height
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    /// Returns `true` if `(x, y)` lies inside the rectangle
+    pub fn contains(&self, x: u32, y: u32) -> bool {
+        x >= self.x && (x as u64) < self.right()
+            && y >= self.y && (y as u64) < self.bottom()
+    }
+
+    /// Returns the overlapping region of `self` and `other`, or `None` if they do not overlap
+    pub fn intersect(&self, other: &Rect) -> Option<Rect> {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let right = self.right().min(other.right());
+        let bottom = self.bottom().min(other.bottom());
+
+        if right <= x as u64 || bottom <= y as u64 {
+            return None;
+        }
+
+        Some(Rect::new(x, y, (right - x as u64) as u32, (bottom - y as u64) as u32))
+    }
+
+    /// Returns the part of the rectangle that lies inside an image of size `width x height`, or
+    /// `None` if the rectangle lies entirely outside the image
+    pub fn clip_to(&self, width: u32, height: u32) -> Option<Rect> {
+        self.intersect(&Rect::new(0, 0, width, height))
+    }
+
+    // Computed as u64 so that rectangles near u32::MAX do not overflow
+    fn right(&self) -> u64 {
+        self.x as u64 + self.width as u64
+    }
+
+    fn bottom(&self) -> u64 {
+        self.y as u64 + self.height as u64
+    }
+}
@@ -0,0 +1,88 @@
+use crate::image::{Image, BaseImage, Number, SubImage};
+
+/// A struct representing a tile iterator for an image. `next()` returns a tuple containing the
+/// x-coordinate and y-coordinate of the upper left hand corner of the tile, and a `SubImage<T>`
+/// representing the tile, in that order. Tiles do not overlap and are returned row by row; tiles
+/// on the right and bottom edges of the image may be smaller than the requested tile size.
+///
+/// # Examples
+/// ```rust
+/// # fn main() {
+/// use imgproc_rs::image::{Image, BaseImage};
+///
+/// // Create an image
+/// let img = Image::from_vec(3, 2, 1, false, vec![1, 2, 3, 4, 5, 6]);
+///
+/// // Print the size of each 2x2 tile along with its position
+/// for (x, y, tile) in img.tiles(2, 2) {
+///     println!("(x: {}, y: {}), size: {:?}", x, y, tile.info().wh());
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct TileIter<'a, T: Number> {
+    image: &'a Image<T>,
+    tile_width: u32,
+    tile_height: u32,
+    x: u32,
+    y: u32,
+}
+
+impl<'a, T: Number> TileIter<'a, T> {
+    /// Creates a new `TileIter<T>` over tiles of size `tile_width x tile_height`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `tile_width` or `tile_height` is zero
+    pub fn new(image: &'a Image<T>, tile_width: u32, tile_height: u32) -> Self {
+        if tile_width == 0 || tile_height == 0 {
+            panic!("invalid tile size: the tile size is {}x{}, but tiles must be non-empty",
+                   tile_width, tile_height);
+        }
+
+        TileIter {
+            image,
+            tile_width,
+            tile_height,
+            x: 0,
+            y: 0,
+        }
+    }
+}
+
+impl<'a, T: Number> Iterator for TileIter<'a, T> {
+    type Item = (u32, u32, SubImage<'a, T>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (width, height) = self.image.info().wh();
+
+        if self.x >= width {
+            self.x = 0;
+            self.y += self.tile_height;
+        }
+        if width == 0 || self.y >= height {
+            return None;
+        }
+
+        let (x, y) = (self.x, self.y);
+        let tile_width = self.tile_width.min(width - x);
+        let tile_height = self.tile_height.min(height - y);
+        self.x += self.tile_width;
+
+        Some((x, y, self.image.subimage(x, y, tile_width, tile_height).unwrap()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (width, height) = self.image.info().wh();
+        if width == 0 || self.y >= height {
+            return (0, Some(0));
+        }
+
+        let cols = width.div_ceil(self.tile_width) as usize;
+        let rows = (height - self.y).div_ceil(self.tile_height) as usize;
+        let done = self.x.div_ceil(self.tile_width) as usize;
+        let size = rows * cols - done;
+
+        (size, Some(size))
+    }
+}
@@ -19,11 +19,15 @@
 //! # }
 //! ```
 
-use crate::error::{ImgIoError, ImgIoResult};
+use crate::error::{ImgIoError, ImgIoResult, ImgProcError, ImgProcResult};
 use crate::image::{Image, BaseImage};
 
 use image::io::Reader;
-use image::{GenericImageView, ColorType, ImageBuffer};
+use image::{GenericImageView, ColorType, ImageBuffer, ImageFormat};
+
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::Path;
 
 /// Extracts channels and alpha from an `image::ColorType`
 fn from_color_type(color: ColorType) -> ImgIoResult<(u8, bool)> {
@@ -46,6 +50,29 @@ pub fn read(filename: &str) -> ImgIoResult<Image<u8>> {
     Ok(Image::from_slice(width, height, channels, alpha, img.as_bytes()))
 }
 
+/// Reads an image file into an `Image<u16>`, such as a 16-bit grayscale PNG or TIFF depth map.
+/// Images with 8-bit channels are scaled to the range 0 to 65535. A wrapper around
+/// `image::io::Reader::open()`
+pub fn read_u16(filename: &str) -> ImgIoResult<Image<u16>> {
+    let img = Reader::open(filename)?.decode()?;
+    let (width, height) = img.dimensions();
+    let color = img.color();
+
+    let (channels, alpha, mut data) = match (color.has_color(), color.has_alpha()) {
+        (false, false) => (1, false, img.to_luma16().into_raw()),
+        (false, true) => (2, true, img.to_luma_alpha16().into_raw()),
+        (true, false) => (3, false, img.to_rgb16().into_raw()),
+        (true, true) => (4, true, img.to_rgba16().into_raw()),
+    };
+
+    // `image` widens 8-bit channels by shifting them, which maps 255 to 65280 rather than 65535
+    if color.bytes_per_pixel() == color.channel_count() {
+        data.iter_mut().for_each(|val| *val |= *val >> 8);
+    }
+
+    Ok(Image::from_vec(width, height, channels, alpha, data))
+}
+
 /// Writes an RGB(A)8 or Gray(A)8 `Image<u8>` into an image file. A wrapper around `image::io::Reader::save()`
 pub fn write(input: &Image<u8>, filename: &str) -> ImgIoResult<()> {
     let (width, height, channels, alpha) = input.info().whca();
@@ -81,4 +108,116 @@ pub fn write(input: &Image<u8>, filename: &str) -> ImgIoResult<()> {
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Writes a single-channel `Image<u8>` of palette indices into a paletted (indexed) 8-bit PNG
+/// file, where each pixel is drawn in the color `palette[index]`. This produces much smaller files
+/// than RGB output for images with few colors
+///
+/// # Arguments
+///
+/// * `input` - Must have a single channel and no alpha channel, and every value must be a valid index into `palette`
+/// * `palette` - Must contain between 1 and 256 colors (inclusive)
+pub fn encode_png_indexed(input: &Image<u8>, palette: &[[u8; 3]], filename: &str) -> ImgIoResult<()> {
+    if input.info().channels != 1 || input.info().alpha {
+        return Err(ImgIoError::UnsupportedColorTypeError("input must be a single-channel image \
+            of palette indices".to_string()));
+    }
+    if palette.is_empty() || palette.len() > 256 {
+        return Err(ImgProcError::InvalidArgError(format!("invalid palette: must contain between \
+            1 and 256 colors, but contains {}", palette.len())).into());
+    }
+    if let Some(index) = input.data().iter().find(|index| **index as usize >= palette.len()) {
+        return Err(ImgProcError::InvalidArgError(format!("invalid index: palette contains {} \
+            colors, but the image contains index {}", palette.len(), index)).into());
+    }
+
+    let (width, height) = input.info().wh();
+    let mut encoder = png::Encoder::new(BufWriter::new(File::create(filename)?), width, height);
+    encoder.set_color(png::ColorType::Indexed);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_palette(palette.concat());
+
+    let mut writer = encoder.write_header()
+        .map_err(|err| ImgIoError::ImageWriteError(err.to_string()))?;
+    writer.write_image_data(input.data())
+        .map_err(|err| ImgIoError::ImageWriteError(err.to_string()))?;
+
+    Ok(())
+}
+
+/// Reads a paletted (indexed) PNG file into a single-channel `Image<u8>` of palette indices and
+/// its palette, without expanding the indices into colors. Indices with fewer than 8 bits are
+/// widened to one byte each
+pub fn read_png_indexed(filename: &str) -> ImgIoResult<(Image<u8>, Vec<[u8; 3]>)> {
+    let mut decoder = png::Decoder::new(File::open(filename)?);
+    decoder.set_transformations(png::Transformations::IDENTITY);
+    let (info, mut reader) = decoder.read_info()
+        .map_err(|err| ImgIoError::OtherError(err.to_string()))?;
+
+    if info.color_type != png::ColorType::Indexed {
+        return Err(ImgIoError::UnsupportedColorTypeError("image is not a paletted PNG".to_string()));
+    }
+
+    let mut buf = vec![0; info.buffer_size()];
+    reader.next_frame(&mut buf)
+        .map_err(|err| ImgIoError::OtherError(err.to_string()))?;
+
+    let palette = reader.info().palette.as_ref()
+        .ok_or_else(|| ImgIoError::OtherError("paletted PNG has no palette".to_string()))?
+        .chunks_exact(3)
+        .map(|color| [color[0], color[1], color[2]])
+        .collect();
+
+    // Unpack indices that are stored in fewer than 8 bits, most significant bits first
+    let bits = info.bit_depth as usize;
+    let mut data = Vec::with_capacity((info.width * info.height) as usize);
+    for row in buf.chunks_exact(info.line_size) {
+        for x in 0..(info.width as usize) {
+            let shift = 8 - bits - (x * bits) % 8;
+            data.push((row[x * bits / 8] >> shift) & (0xff >> (8 - bits)) as u8);
+        }
+    }
+
+    Ok((Image::from_vec(info.width, info.height, 1, false, data), palette))
+}
+
+/// Reads every image in the directory `dir` with a supported file extension, applies `f`, and
+/// writes the result to `out_dir` under the same file name. Returns the file name and result of
+/// each image, sorted by file name; a failure for one image does not stop the others from
+/// being processed. `out_dir` is created if it does not exist
+pub fn process_directory<F>(dir: &str, out_dir: &str, f: F) -> ImgIoResult<Vec<(String, ImgIoResult<()>)>>
+    where F: Fn(&Image<u8>) -> ImgProcResult<Image<u8>> {
+    fs::create_dir_all(out_dir)?;
+
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_file() && ImageFormat::from_path(&path).is_ok() {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    let mut results = Vec::with_capacity(paths.len());
+    for path in paths.iter() {
+        let name = path.file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let out_path = Path::new(out_dir).join(&name);
+
+        let result = path.to_str()
+            .ok_or_else(|| ImgIoError::OtherError(format!("invalid file path: {:?}", path)))
+            .and_then(read)
+            .and_then(|img| Ok(f(&img)?))
+            .and_then(|img| {
+                let out_filename = out_path.to_str()
+                    .ok_or_else(|| ImgIoError::OtherError(format!("invalid file path: {:?}", out_path)))?;
+                write(&img, out_filename)
+            });
+
+        results.push((name, result));
+    }
+
+    Ok(results)
+}
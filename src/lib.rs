@@ -10,3 +10,6 @@ pub mod filter;
 pub mod transform;
 pub mod convert;
 pub mod morphology;
+pub mod analysis;
+pub mod pipeline;
+pub mod segmentation;
@@ -0,0 +1,84 @@
+//! A module for chaining image operations
+//!
+//! # Examples
+//! ```rust
+//! # use imgproc_rs::error::ImgProcResult;
+//! #
+//! # fn main() -> ImgProcResult<()> {
+//! use imgproc_rs::enums::{Scale, Tone};
+//! use imgproc_rs::image::Image;
+//! use imgproc_rs::pipeline::ImagePipeline;
+//!
+//! let img = Image::from_vec(4, 4, 1, false, vec![100; 16]);
+//!
+//! let output = ImagePipeline::new(img)
+//!     .brightness(20, Tone::Rgb)
+//!     .blur(1.0)
+//!     .resize(2, 2, Scale::Bilinear)
+//!     .build()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{filter, tone, transform};
+use crate::enums::{Scale, Tone};
+use crate::error::ImgProcResult;
+use crate::image::Image;
+
+/// A builder that applies a sequence of operations to an `Image<u8>`. Each operation is applied
+/// immediately; once an operation fails, the remaining operations are skipped and the error is
+/// returned by [`build`](#method.build)
+#[derive(Debug)]
+pub struct ImagePipeline {
+    image: ImgProcResult<Image<u8>>,
+}
+
+impl ImagePipeline {
+    /// Creates a new `ImagePipeline` starting from `input`
+    pub fn new(input: Image<u8>) -> Self {
+        ImagePipeline { image: Ok(input) }
+    }
+
+    /// Applies the operation `f`
+    pub fn then<F>(self, f: F) -> Self
+        where F: FnOnce(&Image<u8>) -> ImgProcResult<Image<u8>> {
+        ImagePipeline { image: self.image.and_then(|img| f(&img)) }
+    }
+
+    /// Applies a Gaussian blur with standard deviation `sigma`, using a kernel that covers 3
+    /// standard deviations on each side. See [`filter::gaussian_blur`](../filter/fn.gaussian_blur.html)
+    pub fn blur(self, sigma: f64) -> Self {
+        let size = 2 * (3.0 * sigma).ceil().max(0.0) as u32 + 1;
+        self.then(|img| Ok(filter::gaussian_blur(&img.clone().into(), size, sigma)?.into()))
+    }
+
+    /// Adjusts brightness. See [`tone::brightness`](../tone/fn.brightness.html)
+    pub fn brightness(self, bias: i32, method: Tone) -> Self {
+        self.then(|img| tone::brightness(img, bias, method))
+    }
+
+    /// Adjusts contrast. See [`tone::contrast`](../tone/fn.contrast.html)
+    pub fn contrast(self, gain: f64, method: Tone) -> Self {
+        self.then(|img| tone::contrast(img, gain, method))
+    }
+
+    /// Adjusts saturation. See [`tone::saturation`](../tone/fn.saturation.html)
+    pub fn saturation(self, saturation: i32) -> Self {
+        self.then(|img| tone::saturation(img, saturation))
+    }
+
+    /// Applies gamma correction. See [`tone::gamma`](../tone/fn.gamma.html)
+    pub fn gamma(self, gamma: f64, max: u8) -> Self {
+        self.then(|img| tone::gamma(img, gamma, max))
+    }
+
+    /// Resizes the image to `width x height`. See [`transform::resize`](../transform/fn.resize.html)
+    pub fn resize(self, width: u32, height: u32, method: Scale) -> Self {
+        self.then(|img| Ok(transform::resize(&img.clone().into(), width, height, method)?.into()))
+    }
+
+    /// Returns the resulting image, or the error from the first operation that failed
+    pub fn build(self) -> ImgProcResult<Image<u8>> {
+        self.image
+    }
+}
@@ -0,0 +1,175 @@
+//! A module for image segmentation
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use crate::{analysis, colorspace, error, filter};
+use crate::enums::White;
+use crate::error::{ImgProcError, ImgProcResult};
+use crate::image::{BaseImage, Image};
+
+/// Segments `input` with a marker-controlled watershed on its gradient magnitude. `input` is
+/// converted to grayscale, and the Sobel gradient magnitude is flooded from the labeled pixels of
+/// `markers` (a single-channel image with the same dimensions as `input`, where 0 is unlabeled)
+/// in order of increasing gradient. Each output pixel holds the label of the marker its basin
+/// was flooded from; pixels where two basins meet form the watershed lines and are set to 0, as
+/// are pixels that cannot be reached from any marker
+pub fn watershed(input: &Image<u8>, markers: &Image<u32>) -> ImgProcResult<Image<u32>> {
+    error::check_dimensions_equal(input.info().wh(), markers.info().wh())?;
+    error::check_equal(markers.info().channels, 1, "markers channels")?;
+
+    let (width, height) = input.info().wh();
+    let gray: Image<f64> = analysis::to_grayscale(input).into();
+    let gradient = filter::sobel(&gray)?;
+    let mut labels = markers.data().to_vec();
+    let mut queued: Vec<bool> = labels.iter().map(|label| *label != 0).collect();
+
+    // Gradient magnitudes are non-negative, so their bit patterns sort in the same order as their
+    // values. The counter makes pixels with equal gradients flood in the order they were queued
+    let mut queue = BinaryHeap::new();
+    let mut count = 0u64;
+    let mut push = |queue: &mut BinaryHeap<_>, queued: &mut [bool], i: usize| {
+        for n in neighbors(i, width, height).iter().flatten() {
+            if !queued[*n] {
+                queued[*n] = true;
+                queue.push(Reverse((gradient[*n][0].to_bits(), count, *n)));
+                count += 1;
+            }
+        }
+    };
+
+    for (i, label) in labels.iter().enumerate() {
+        if *label != 0 {
+            push(&mut queue, &mut queued, i);
+        }
+    }
+
+    while let Some(Reverse((_, _, i))) = queue.pop() {
+        let mut label = 0;
+        let mut is_line = false;
+
+        for n in neighbors(i, width, height).iter().flatten() {
+            if labels[*n] != 0 {
+                if label == 0 {
+                    label = labels[*n];
+                } else if label != labels[*n] {
+                    is_line = true;
+                }
+            }
+        }
+
+        if !is_line && label != 0 {
+            labels[i] = label;
+            push(&mut queue, &mut queued, i);
+        }
+    }
+
+    Ok(Image::from_vec(width, height, 1, false, labels))
+}
+
+/// Segments an RGB or RGBA image into superpixels using simple linear iterative clustering (SLIC).
+/// Cluster centers start on a grid with spacing `region_size`, and pixels are clustered with
+/// k-means in combined CIELAB and image space, where each center only considers pixels within
+/// `region_size` of it. Each output pixel holds the index of its superpixel
+///
+/// # Arguments
+///
+/// * `input` - the image to segment
+/// * `region_size` - the approximate side length of each superpixel; must be at least 2
+/// * `compactness` - the weight of spatial distance relative to color distance
+pub fn slic(input: &Image<u8>, region_size: u32, compactness: f64) -> ImgProcResult<Image<u32>> {
+    if region_size < 2 {
+        return Err(ImgProcError::InvalidArgError("region_size must be at least 2".to_string()));
+    }
+    error::check_non_neg(compactness, "compactness")?;
+    error::check_non_empty(input)?;
+    error::check_rgb(input)?;
+
+    const ITERATIONS: u32 = 10;
+
+    let (width, height) = input.info().wh();
+    let lab = colorspace::srgb_to_lab(input, &White::D65);
+    let step = region_size as f64;
+    let (cols, rows) = (width.div_ceil(region_size), height.div_ceil(region_size));
+
+    // Each center is [L*, a*, b*, x, y]
+    let mut centers = Vec::with_capacity((cols * rows) as usize);
+    for j in 0..rows {
+        for i in 0..cols {
+            let x = (i * region_size + region_size / 2).min(width - 1);
+            let y = (j * region_size + region_size / 2).min(height - 1);
+            let p = lab.get_pixel_unchecked(x, y);
+            centers.push([p[0], p[1], p[2], x as f64, y as f64]);
+        }
+    }
+
+    let mut labels: Vec<u32> = (0..(width * height))
+        .map(|i| (i / width / region_size) * cols + (i % width) / region_size)
+        .collect();
+    let mut distances = vec![f64::INFINITY; labels.len()];
+
+    for _ in 0..ITERATIONS {
+        for dist in distances.iter_mut() {
+            *dist = f64::INFINITY;
+        }
+
+        for (k, center) in centers.iter().enumerate() {
+            let x_min = (center[3] - step).max(0.0).ceil() as u32;
+            let x_max = (center[3] + step).min((width - 1) as f64).floor() as u32;
+            let y_min = (center[4] - step).max(0.0).ceil() as u32;
+            let y_max = (center[4] + step).min((height - 1) as f64).floor() as u32;
+
+            for y in y_min..=y_max {
+                for x in x_min..=x_max {
+                    let p = lab.get_pixel_unchecked(x, y);
+                    let color = (p[0] - center[0]).powi(2) + (p[1] - center[1]).powi(2) + (p[2] - center[2]).powi(2);
+                    let space = (x as f64 - center[3]).powi(2) + (y as f64 - center[4]).powi(2);
+                    let dist = color + space / (step * step) * compactness * compactness;
+
+                    let i = (y * width + x) as usize;
+                    if dist < distances[i] {
+                        distances[i] = dist;
+                        labels[i] = k as u32;
+                    }
+                }
+            }
+        }
+
+        let mut sums = vec![[0.0; 6]; centers.len()];
+        for (i, label) in labels.iter().enumerate() {
+            let (x, y) = (i as u32 % width, i as u32 / width);
+            let p = lab.get_pixel_unchecked(x, y);
+            let sum = &mut sums[*label as usize];
+
+            sum[0] += p[0];
+            sum[1] += p[1];
+            sum[2] += p[2];
+            sum[3] += x as f64;
+            sum[4] += y as f64;
+            sum[5] += 1.0;
+        }
+
+        for (center, sum) in centers.iter_mut().zip(sums.iter()) {
+            if sum[5] > 0.0 {
+                for (c, val) in center.iter_mut().enumerate() {
+                    *val = sum[c] / sum[5];
+                }
+            }
+        }
+    }
+
+    Ok(Image::from_vec(width, height, 1, false, labels))
+}
+
+// Returns the indices of the 4-connected neighbors of the pixel at index `i`
+fn neighbors(i: usize, width: u32, height: u32) -> [Option<usize>; 4] {
+    let (width, height) = (width as usize, height as usize);
+    let (x, y) = (i % width, i / width);
+
+    [
+        if x > 0 { Some(i - 1) } else { None },
+        if x + 1 < width { Some(i + 1) } else { None },
+        if y > 0 { Some(i - width) } else { None },
+        if y + 1 < height { Some(i + width) } else { None },
+    ]
+}
@@ -1,8 +1,8 @@
 //! A module for image tone operations
 
-use crate::{util, colorspace, error};
+use crate::{util, colorspace, error, filter};
 use crate::enums::{Tone, White};
-use crate::image::Image;
+use crate::image::{BaseImage, Image, ImageInfo};
 use crate::error::ImgProcResult;
 
 use std::collections::HashMap;
@@ -18,21 +18,36 @@ pub fn brightness(input: &Image<u8>, bias: i32, method: Tone) -> ImgProcResult<I
 
     match method {
         Tone::Rgb => {
-            let mut lookup_table: [u8; 256] = [0; 256];
-            util::generate_lookup_table(&mut lookup_table, |i| {
-                (i as i32 + bias).clamp(0, 255) as u8
-            });
-
+            let lookup_table = brightness_table(bias);
             Ok(input.map_channels_if_alpha(|channel| lookup_table[channel as usize], |a| a))
         },
         Tone::Lab => {
-            let mut lab = colorspace::srgb_to_lab(input, &White::D50);
-            lab.edit_channel(|num| num + (bias as f64) * 255.0 / 100.0, 0);
-            Ok(colorspace::lab_to_srgb(&lab, &White::D50))
+            let lab = colorspace::srgb_to_lab(input, &White::D50);
+            Ok(restore_grayscale(input, colorspace::lab_to_srgb(&brightness_lab(&lab, bias)?, &White::D50)))
         },
     }
 }
 
+/// Adjusts brightness in place. See [`brightness`](fn.brightness.html). If `method` is
+/// `Tone::Rgb`, no new image is allocated
+///
+/// # Arguments
+///
+/// * `bias` - Must be between 0 and 255 (inclusive)
+pub fn brightness_mut(input: &mut Image<u8>, bias: i32, method: Tone) -> ImgProcResult<()> {
+    match method {
+        Tone::Rgb => {
+            error::check_in_range(bias, 0, 255, "bias")?;
+
+            let lookup_table = brightness_table(bias);
+            input.apply_channels_if_alpha(|channel| lookup_table[channel as usize], |a| a);
+        },
+        Tone::Lab => *input = brightness(input, bias, method)?,
+    }
+
+    Ok(())
+}
+
 /// Adjusts contrast by multiplying each RGB channel by `gain` if `method` is `Tone::Rgb`, or
 /// multiplying the L* channel of `input` in CIELAB by `gain` if `method` is `Tone::Lab`
 ///
@@ -44,22 +59,82 @@ pub fn contrast(input: &Image<u8>, gain: f64, method: Tone) -> ImgProcResult<Ima
 
     match method {
         Tone::Rgb => {
-            let mut lookup_table: [u8; 256] = [0; 256];
-            util::generate_lookup_table(&mut lookup_table, |i| {
-                (i as f64 * gain).round().clamp(0.0, 255.0) as u8
-            });
-
+            let lookup_table = contrast_table(gain, 0);
             Ok(input.map_channels_if_alpha(|channel| lookup_table[channel as usize], |a| a))
         },
         Tone::Lab => {
-            let mut lab = colorspace::srgb_to_lab(input, &White::D50);
-            lab.edit_channel(|num| num * gain, 0);
-            Ok(colorspace::lab_to_srgb(&lab, &White::D50))
+            let lab = colorspace::srgb_to_lab(input, &White::D50);
+            Ok(restore_grayscale(input, colorspace::lab_to_srgb(&contrast_lab(&lab, gain)?, &White::D50)))
+        },
+    }
+}
+
+/// Adjusts contrast in place. See [`contrast`](fn.contrast.html). If `method` is `Tone::Rgb`, no
+/// new image is allocated
+///
+/// # Arguments
+///
+/// * `gain` - Must be non-negative
+pub fn contrast_mut(input: &mut Image<u8>, gain: f64, method: Tone) -> ImgProcResult<()> {
+    match method {
+        Tone::Rgb => {
+            error::check_non_neg(gain, "gain")?;
+
+            let lookup_table = contrast_table(gain, 0);
+            input.apply_channels_if_alpha(|channel| lookup_table[channel as usize], |a| a);
         },
+        Tone::Lab => *input = contrast(input, gain, method)?,
     }
+
+    Ok(())
+}
+
+/// Adjusts contrast by scaling each RGB channel about `pivot` by `gain`, so that each channel
+/// value `v` becomes `pivot + (v - pivot) * gain`. Unlike [`contrast`](fn.contrast.html), values
+/// equal to `pivot` are unchanged; a `pivot` of 128 gives the standard photographic contrast
+///
+/// # Arguments
+///
+/// * `gain` - Must be non-negative
+pub fn contrast_pivot(input: &Image<u8>, gain: f64, pivot: u8) -> ImgProcResult<Image<u8>> {
+    error::check_non_neg(gain, "gain")?;
+
+    let lookup_table = contrast_table(gain, pivot);
+    Ok(input.map_channels_if_alpha(|channel| lookup_table[channel as usize], |a| a))
+}
+
+/// Adjusts brightness by adding `bias` to the L* channel of a CIELAB image. This allows
+/// several adjustments to be applied without converting to and from sRGB each time
+///
+/// # Arguments
+///
+/// * `bias` - Must be between 0 and 255 (inclusive)
+pub fn brightness_lab(input: &Image<f64>, bias: i32) -> ImgProcResult<Image<f64>> {
+    error::check_in_range(bias, 0, 255, "bias")?;
+
+    let mut output = input.clone();
+    output.edit_channel(|num| num + (bias as f64) * 255.0 / 100.0, 0);
+
+    Ok(output)
 }
 
-/// Adjusts saturation by adding `saturation` to the saturation value (S) of `input` in HSV
+/// Adjusts contrast by multiplying the L* channel of a CIELAB image by `gain`. This allows
+/// several adjustments to be applied without converting to and from sRGB each time
+///
+/// # Arguments
+///
+/// * `gain` - Must be non-negative
+pub fn contrast_lab(input: &Image<f64>, gain: f64) -> ImgProcResult<Image<f64>> {
+    error::check_non_neg(gain, "gain")?;
+
+    let mut output = input.clone();
+    output.edit_channel(|num| num * gain, 0);
+
+    Ok(output)
+}
+
+/// Adjusts saturation by adding `saturation` to the saturation value (S) of `input` in HSV.
+/// Grayscale images are returned unchanged
 ///
 /// # Arguments
 ///
@@ -67,6 +142,10 @@ pub fn contrast(input: &Image<u8>, gain: f64, method: Tone) -> ImgProcResult<Ima
 pub fn saturation(input: &Image<u8>, saturation: i32) -> ImgProcResult<Image<u8>> {
     error::check_in_range(saturation, 0, 255, "saturation")?;
 
+    if input.info().channels_non_alpha() == 1 {
+        return Ok(input.clone());
+    }
+
     let mut hsv = colorspace::rgb_to_hsv(input);
     hsv.edit_channel(|s| (s + (saturation as f64 / 255.0)) as f64, 1);
 
@@ -81,9 +160,40 @@ pub fn saturation(input: &Image<u8>, saturation: i32) -> ImgProcResult<Image<u8>
 pub fn gamma(input: &Image<u8>, gamma: f64, max: u8) -> ImgProcResult<Image<u8>> {
     error::check_non_neg(gamma, "gamma")?;
 
-    Ok(input.map_channels_if_alpha(|channel| {
-        ((channel as f64 / max as f64).powf(gamma) * (max as f64)).round() as u8
-    }, |a| a))
+    let lookup_table = gamma_table(gamma, max);
+    Ok(input.map_channels_if_alpha(|channel| lookup_table[channel as usize], |a| a))
+}
+
+/// Performs a gamma correction in place without allocating a new image. See
+/// [`gamma`](fn.gamma.html)
+///
+/// # Arguments
+///
+/// * `gamma` - Must be non-negative
+pub fn gamma_mut(input: &mut Image<u8>, gamma: f64, max: u8) -> ImgProcResult<()> {
+    error::check_non_neg(gamma, "gamma")?;
+
+    let lookup_table = gamma_table(gamma, max);
+    input.apply_channels_if_alpha(|channel| lookup_table[channel as usize], |a| a);
+
+    Ok(())
+}
+
+/// Encodes a linear light image with the sRGB transfer function. Unlike
+/// [`linear_to_srgb`](../colorspace/fn.linear_to_srgb.html), both input and output have channels
+/// in range [0, 255]
+pub fn srgb_encode(input: &Image<u8>) -> Image<u8> {
+    let lookup_table = srgb_table(util::srgb_encode_fn);
+    input.map_channels_if_alpha(|channel| lookup_table[channel as usize], |a| a)
+}
+
+/// Decodes an sRGB image into linear light with the sRGB transfer function, reversing
+/// [`srgb_encode`](fn.srgb_encode.html). Unlike
+/// [`srgb_to_linear`](../colorspace/fn.srgb_to_linear.html), both input and output have channels
+/// in range [0, 255], so dark tones are quantized
+pub fn srgb_decode(input: &Image<u8>) -> Image<u8> {
+    let lookup_table = srgb_table(util::srgb_decode_fn);
+    input.map_channels_if_alpha(|channel| lookup_table[channel as usize], |a| a)
 }
 
 /// Performs a histogram equalization on `input`
@@ -109,5 +219,341 @@ pub fn histogram_equalization(input: &Image<u8>, alpha: f64, ref_white: &White,
         (alpha * percentiles.get(&key).unwrap() * 100.0) + ((1.0 - alpha) * num)
     }, 0);
 
-    Ok(colorspace::lab_to_srgb(&lab, ref_white))
+    Ok(restore_grayscale(input, colorspace::lab_to_srgb(&lab, ref_white)))
+}
+
+/// Remaps each color channel of `input` so that its cumulative distribution matches that of the
+/// corresponding channel of `reference`. Each input value is mapped to the smallest reference
+/// value whose cumulative frequency is at least as large. `input` and `reference` may have
+/// different dimensions, but must have the same number of color channels. Alpha channels are
+/// left unchanged
+pub fn histogram_match(input: &Image<u8>, reference: &Image<u8>) -> ImgProcResult<Image<u8>> {
+    error::check_non_empty(input)?;
+    error::check_non_empty(reference)?;
+    error::check_equal(input.info().channels_non_alpha(), reference.info().channels_non_alpha(),
+                       "color channels")?;
+
+    let cdfs_in = channel_cdfs(input);
+    let cdfs_ref = channel_cdfs(reference);
+
+    let lookup_tables: Vec<[u8; 256]> = cdfs_in.iter().zip(cdfs_ref.iter())
+        .map(|(cdf_in, cdf_ref)| {
+            let mut lookup_table: [u8; 256] = [0; 256];
+            let mut r = 0;
+            for (v, entry) in lookup_table.iter_mut().enumerate() {
+                while r < 255 && cdf_ref[r] < cdf_in[v] {
+                    r += 1;
+                }
+                *entry = r as u8;
+            }
+
+            lookup_table
+        })
+        .collect();
+
+    Ok(input.map_pixels_if_alpha(|channels, p_out| {
+        for (c, channel) in channels.iter().enumerate() {
+            p_out.push(lookup_tables[c][*channel as usize]);
+        }
+    }, |a| a))
+}
+
+/// Enhances local contrast using single-scale retinex, which subtracts the logarithm of a
+/// Gaussian blur with standard deviation `sigma` from the logarithm of each channel and rescales
+/// the result to fill the range 0 to 255. This brings out detail in dark regions next to bright
+/// ones. Alpha channels are left unchanged
+///
+/// # Arguments
+///
+/// * `sigma` - Must be positive
+pub fn single_scale_retinex(input: &Image<u8>, sigma: f64) -> ImgProcResult<Image<u8>> {
+    let img: Image<f64> = input.clone().into();
+    Ok(rescale_retinex(retinex(&img, sigma)?, false))
+}
+
+/// Enhances local contrast using multi-scale retinex with color restoration (MSRCR). The
+/// single-scale retinex (see [`single_scale_retinex`](fn.single_scale_retinex.html)) is averaged
+/// over each standard deviation in `sigmas`, and each channel is then weighted by the logarithm of
+/// its share of the pixel's total intensity. This restores the saturation that retinex alone
+/// washes out of uniformly colored regions. Each channel of the result is then rescaled
+/// independently to fill the range 0 to 255, which also removes color casts. Alpha channels are
+/// left unchanged
+///
+/// # Arguments
+///
+/// * `sigmas` - Must not be empty, and each must be positive
+pub fn msrcr(input: &Image<u8>, sigmas: &[f64]) -> ImgProcResult<Image<u8>> {
+    error::check_non_empty_slice(sigmas, "sigmas")?;
+
+    let img: Image<f64> = input.clone().into();
+    let mut msr = retinex(&img, sigmas[0])?;
+    for sigma in sigmas[1..].iter() {
+        msr = msr.zip_map(&retinex(&img, *sigma)?, |p_1, p_2, p_out| {
+            for (val_1, val_2) in p_1.iter().zip(p_2.iter()) {
+                p_out.push(val_1 + val_2);
+            }
+        })?;
+    }
+
+    // Scale the averaged retinex by the color restoration function log(125 * I_c / sum(I))
+    let channels_non_alpha = input.info().channels_non_alpha() as usize;
+    let n = sigmas.len() as f64;
+    let msrcr = msr.zip_map(&img, |p_msr, p, p_out| {
+        let sum: f64 = p[..channels_non_alpha].iter().map(|val| val + 1.0).sum();
+        for (c, val) in p_msr.iter().enumerate() {
+            if c < channels_non_alpha {
+                p_out.push(val / n * (125.0 * (p[c] + 1.0) / sum).ln());
+            } else {
+                p_out.push(p[c]);
+            }
+        }
+    })?;
+
+    Ok(rescale_retinex(msrcr, true))
+}
+
+/// Maps an image with unbounded linear channel values to the range 0 to 255 using the global
+/// Reinhard operator, which compresses the luminance `L` to `L / (1 + L)` and scales each channel
+/// by the same factor to preserve color. The luminance of an RGB image is computed from its red,
+/// green and blue channels; the luminance of a grayscale image is its only channel. Alpha
+/// channels are copied unchanged
+pub fn reinhard_tonemap(input: &Image<f64>) -> Image<u8> {
+    let channels_non_alpha = input.info().channels_non_alpha() as usize;
+
+    let output = input.map_pixels(|p, p_out| {
+        let lum = if channels_non_alpha >= 3 {
+            0.2126 * p[0] + 0.7152 * p[1] + 0.0722 * p[2]
+        } else {
+            p[0]
+        }.max(0.0);
+        let scale = if lum > 0.0 { 1.0 / (1.0 + lum) } else { 0.0 };
+
+        for (c, val) in p.iter().enumerate() {
+            if c < channels_non_alpha {
+                p_out.push(val * scale * 255.0);
+            } else {
+                p_out.push(*val);
+            }
+        }
+    });
+
+    output.into()
+}
+
+/// Estimates the background illumination of `input` by averaging each `block x block` region
+/// and bilinearly interpolating between the block averages. The result is a smooth image with the
+/// same dimensions as `input`. Alpha channels are copied from `input`
+pub fn estimate_background(input: &Image<u8>, block: u32) -> ImgProcResult<Image<u8>> {
+    Ok(background(input, block)?.into())
+}
+
+/// Corrects uneven lighting by dividing `input` by its estimated background illumination (see
+/// [`estimate_background`](fn.estimate_background.html)) and rescaling each channel so that its
+/// mean brightness is preserved
+pub fn flatten_illumination(input: &Image<u8>, block: u32) -> ImgProcResult<Image<u8>> {
+    let bg = background(input, block)?;
+    let (width, height, channels) = input.info().whc();
+    let channels_non_alpha = input.info().channels_non_alpha() as usize;
+
+    let mut means = vec![0.0; channels_non_alpha];
+    for p in bg.data().chunks(channels as usize) {
+        for c in 0..channels_non_alpha {
+            means[c] += p[c] / (width * height) as f64;
+        }
+    }
+
+    let mut output = input.clone();
+    for y in 0..height {
+        for x in 0..width {
+            let p_bg = bg.get_pixel_unchecked(x, y);
+            let p_out = output.get_pixel_mut_unchecked(x, y);
+
+            for c in 0..channels_non_alpha {
+                p_out[c] = (p_out[c] as f64 * means[c] / p_bg[c].max(1.0)).round().clamp(0.0, 255.0) as u8;
+            }
+        }
+    }
+
+    Ok(output)
+}
+
+// Computes the single-scale retinex of each non-alpha channel of `input`. Alpha channels are
+// copied unchanged
+fn retinex(input: &Image<f64>, sigma: f64) -> ImgProcResult<Image<f64>> {
+    error::check_positive(sigma, "sigma")?;
+
+    let size = 2 * (3.0 * sigma).ceil() as u32 + 1;
+    let blurred = filter::gaussian_blur(input, size, sigma)?;
+
+    // 1 is added before taking logarithms so that black pixels stay finite
+    let channels_non_alpha = input.info().channels_non_alpha() as usize;
+    input.zip_map(&blurred, |p, p_blur, p_out| {
+        for c in 0..p.len() {
+            if c < channels_non_alpha {
+                p_out.push((p[c] + 1.0).ln() - (p_blur[c] + 1.0).ln());
+            } else {
+                p_out.push(p[c]);
+            }
+        }
+    })
+}
+
+// Linearly rescales the non-alpha channels of `input` so that they fill the range 0 to 255. If
+// `per_channel` is true, each channel is rescaled independently
+fn rescale_retinex(mut input: Image<f64>, per_channel: bool) -> Image<u8> {
+    let channels_non_alpha = input.info().channels_non_alpha() as usize;
+
+    let mut mins = vec![f64::MAX; channels_non_alpha];
+    let mut maxs = vec![f64::MIN; channels_non_alpha];
+    for p in input.data().chunks(input.info().channels as usize) {
+        for c in 0..channels_non_alpha {
+            mins[c] = mins[c].min(p[c]);
+            maxs[c] = maxs[c].max(p[c]);
+        }
+    }
+
+    if !per_channel {
+        let min = mins.iter().cloned().fold(f64::MAX, f64::min);
+        let max = maxs.iter().cloned().fold(f64::MIN, f64::max);
+        mins = vec![min; channels_non_alpha];
+        maxs = vec![max; channels_non_alpha];
+    }
+
+    let channels = input.info().channels as usize;
+    for p in input.data_mut().chunks_mut(channels) {
+        for c in 0..channels_non_alpha {
+            p[c] = (p[c] - mins[c]) * 255.0 / (maxs[c] - mins[c]).max(f64::EPSILON);
+        }
+    }
+
+    input.into()
+}
+
+// Computes the background illumination estimate of `input` on the 0 to 255 scale
+fn background(input: &Image<u8>, block: u32) -> ImgProcResult<Image<f64>> {
+    error::check_non_empty(input)?;
+    error::check_positive(block, "block")?;
+
+    let (width, height, channels, alpha) = input.info().whca();
+    let (blocks_x, blocks_y) = (width.div_ceil(block), height.div_ceil(block));
+    let channels_non_alpha = input.info().channels_non_alpha() as usize;
+
+    // Average each block, including partial blocks on the right and bottom edges
+    let mut averages: Image<f64> = Image::blank(ImageInfo::new(blocks_x, blocks_y, channels_non_alpha as u8, false));
+    for y in 0..height {
+        for x in 0..width {
+            let p_in = input.get_pixel_unchecked(x, y);
+            let count = (block.min(width - (x / block) * block) * block.min(height - (y / block) * block)) as f64;
+            let p_avg = averages.get_pixel_mut_unchecked(x / block, y / block);
+
+            for c in 0..channels_non_alpha {
+                p_avg[c] += p_in[c] as f64 / count;
+            }
+        }
+    }
+
+    // Block averages are located at the block centers
+    let locate = |i: u32, blocks: u32| {
+        let pos = ((i as f64 + 0.5) / block as f64 - 0.5).clamp(0.0, (blocks - 1) as f64);
+        let i_0 = pos.floor() as u32;
+        (i_0, (i_0 + 1).min(blocks - 1), pos - i_0 as f64)
+    };
+
+    let mut output = Image::blank(ImageInfo::new(width, height, channels, alpha));
+    for y in 0..height {
+        let (y_0, y_1, t_y) = locate(y, blocks_y);
+
+        for x in 0..width {
+            let (x_0, x_1, t_x) = locate(x, blocks_x);
+            let p_00 = averages.get_pixel_unchecked(x_0, y_0);
+            let p_10 = averages.get_pixel_unchecked(x_1, y_0);
+            let p_01 = averages.get_pixel_unchecked(x_0, y_1);
+            let p_11 = averages.get_pixel_unchecked(x_1, y_1);
+            let p_out = output.get_pixel_mut_unchecked(x, y);
+
+            for c in 0..channels_non_alpha {
+                let top = p_00[c] + t_x * (p_10[c] - p_00[c]);
+                let bottom = p_01[c] + t_x * (p_11[c] - p_01[c]);
+                p_out[c] = top + t_y * (bottom - top);
+            }
+            if alpha {
+                p_out[channels as usize - 1] = input.get_pixel_unchecked(x, y)[channels as usize - 1] as f64;
+            }
+        }
+    }
+
+    Ok(output)
+}
+
+// Computes the normalized cumulative histogram of each color channel of `input`
+fn channel_cdfs(input: &Image<u8>) -> Vec<[f64; 256]> {
+    let channels = input.info().channels as usize;
+    let channels_non_alpha = input.info().channels_non_alpha() as usize;
+
+    let mut cdfs = vec![[0.0; 256]; channels_non_alpha];
+    for p in input.data().chunks(channels) {
+        for (c, cdf) in cdfs.iter_mut().enumerate() {
+            cdf[p[c] as usize] += 1.0;
+        }
+    }
+
+    let size = input.info().size() as f64;
+    for cdf in cdfs.iter_mut() {
+        let mut sum = 0.0;
+        for val in cdf.iter_mut() {
+            sum += *val;
+            *val = sum / size;
+        }
+    }
+
+    cdfs
+}
+
+fn brightness_table(bias: i32) -> [u8; 256] {
+    let mut lookup_table: [u8; 256] = [0; 256];
+    util::generate_lookup_table(&mut lookup_table, |i| {
+        (i as i32 + bias).clamp(0, 255) as u8
+    });
+
+    lookup_table
+}
+
+fn contrast_table(gain: f64, pivot: u8) -> [u8; 256] {
+    let mut lookup_table: [u8; 256] = [0; 256];
+    util::generate_lookup_table(&mut lookup_table, |i| {
+        (pivot as f64 + (i as f64 - pivot as f64) * gain).round().clamp(0.0, 255.0) as u8
+    });
+
+    lookup_table
+}
+
+fn gamma_table(gamma: f64, max: u8) -> [u8; 256] {
+    let mut lookup_table: [u8; 256] = [0; 256];
+    util::generate_lookup_table(&mut lookup_table, |i| {
+        ((i as f64 / max as f64).powf(gamma) * (max as f64)).round() as u8
+    });
+
+    lookup_table
+}
+
+fn srgb_table<F: Fn(f64) -> f64>(f: F) -> [u8; 256] {
+    let mut lookup_table: [u8; 256] = [0; 256];
+    util::generate_lookup_table(&mut lookup_table, |i| {
+        (f(i as f64 / 255.0) * 255.0).round().clamp(0.0, 255.0) as u8
+    });
+
+    lookup_table
+}
+
+// Colorspace conversions treat a grayscale image as RGB, so this converts `output` back to
+// grayscale (by averaging its color channels) if `input` is grayscale
+fn restore_grayscale(input: &Image<u8>, output: Image<u8>) -> Image<u8> {
+    if input.info().channels_non_alpha() != 1 {
+        return output;
+    }
+
+    output.map_pixels_if_alpha(|channels, p_out| {
+        let sum: f64 = channels.iter().map(|channel| *channel as f64).sum();
+        p_out.push((sum / channels.len() as f64).round() as u8);
+    }, |a| a)
 }
@@ -1,13 +1,18 @@
 //! A module for image transformation operations
 
+use std::f64::consts::PI;
+
+use rulinalg::matrix::Matrix;
+use rulinalg::vector::Vector;
+
 #[cfg(feature = "rayon")]
 use rayon::prelude::*;
 
-use crate::enums::{Refl, Scale};
+use crate::enums::{Anchor, Orientation, Refl, Scale};
 use crate::error;
 use crate::error::{ImgProcError, ImgProcResult};
-use crate::image::{BaseImage, Image, ImageInfo, Number};
-use crate::util;
+use crate::image::{BaseImage, Image, ImageInfo, Number, Rect};
+use crate::{analysis, colorspace, filter, util};
 
 /// Crops an image to a rectangle with upper left corner located at `(x, y)` with width `width`
 /// and height `height`
@@ -57,6 +62,53 @@ pub fn crop<T: Number>(input: &Image<T>, x: u32, y: u32, width: u32, height: u32
     Ok(Image::from_vec_of_slice(width, height, input.info().channels, input.info().alpha, data))
 }
 
+/// Crops an image to the region covered by `rect`
+pub fn crop_rect<T: Number>(input: &Image<T>, rect: Rect) -> ImgProcResult<Image<T>> {
+    crop(input, rect.x, rect.y, rect.width, rect.height)
+}
+
+/// Crops away the uniform border of an image. A border row or column is one in which every
+/// pixel is within `tolerance` of the upper left corner pixel in every channel, or, for images
+/// with an alpha channel, is fully transparent. Returns a copy of `input` if every pixel belongs
+/// to the border
+pub fn trim(input: &Image<u8>, tolerance: u8) -> ImgProcResult<Image<u8>> {
+    error::check_non_empty(input)?;
+
+    let (width, height, channels, alpha) = input.info().whca();
+    let corner = input.get_pixel(0, 0);
+    let is_border = |p: &[u8]| {
+        (alpha && p[channels as usize - 1] == 0)
+            || p.iter().zip(corner.iter()).all(|(a, b)| a.abs_diff(*b) <= tolerance)
+    };
+
+    let mut bounds: Option<Rect> = None;
+    for y in 0..height {
+        for x in 0..width {
+            if is_border(input.get_pixel_unchecked(x, y)) {
+                continue;
+            }
+
+            bounds = Some(match bounds {
+                None => Rect::new(x, y, 1, 1),
+                Some(r) => {
+                    let (x_min, y_min) = (r.x.min(x), r.y.min(y));
+                    let x_max = (r.x + r.width).max(x + 1);
+                    let y_max = (r.y + r.height).max(y + 1);
+                    Rect::new(x_min, y_min, x_max - x_min, y_max - y_min)
+                },
+            });
+        }
+    }
+
+    match bounds {
+        None => Ok(input.clone()),
+        Some(rect) => {
+            let sub = input.subimage_rect(rect)?;
+            Ok(Image::from_vec(rect.width, rect.height, channels, alpha, sub.to_vec()))
+        },
+    }
+}
+
 /// Aligns the top left corner of `front` onto the location `(x, y)` on `back` and superimposes
 /// the two images with weight `alpha` for pixel values of `back` and weight 1 - `alpha` for
 /// pixel values of `front`
@@ -102,12 +154,117 @@ pub fn overlay<T: Number>(back: &Image<T>, front: &Image<T>, x: u32, y: u32) ->
     Ok(output)
 }
 
+/// Aligns the top left corner of `top` onto the location `(x, y)` on `base` and draws `top` over
+/// `base` in place. `(x, y)` may be negative, and any part of `top` that falls outside of `base`
+/// is ignored. If `top` has an alpha channel, it is blended over `base` using its alpha channel;
+/// otherwise, its pixels are copied directly. `top` and `base` must have the same number of
+/// non-alpha channels
+pub fn overlay_mut(base: &mut Image<u8>, top: &Image<u8>, x: i32, y: i32) -> ImgProcResult<()> {
+    error::check_equal(base.info().channels_non_alpha(), top.info().channels_non_alpha(), "non-alpha channels")?;
+
+    let channels = top.info().channels_non_alpha() as usize;
+    let (base_alpha, top_alpha) = (base.info().alpha, top.info().alpha);
+    let x_start = x.max(0) as i64;
+    let y_start = y.max(0) as i64;
+    let x_end = (x as i64 + top.info().width as i64).min(base.info().width as i64);
+    let y_end = (y as i64 + top.info().height as i64).min(base.info().height as i64);
+
+    for j in y_start..y_end {
+        for i in x_start..x_end {
+            let p_top = top.get_pixel_unchecked((i - x as i64) as u32, (j - y as i64) as u32);
+            let p_base = base.get_pixel_mut_unchecked(i as u32, j as u32);
+
+            if !top_alpha {
+                p_base[..channels].copy_from_slice(p_top);
+                if base_alpha {
+                    p_base[channels] = 255;
+                }
+                continue;
+            }
+
+            // Source-over compositing with straight (non-premultiplied) alpha
+            let a_top = p_top[channels] as f64 / 255.0;
+            let a_base = if base_alpha { p_base[channels] as f64 / 255.0 } else { 1.0 };
+            let a_out = a_top + a_base * (1.0 - a_top);
+            if a_out == 0.0 {
+                continue;
+            }
+
+            for c in 0..channels {
+                let val = (p_top[c] as f64 * a_top + p_base[c] as f64 * a_base * (1.0 - a_top)) / a_out;
+                p_base[c] = val.round() as u8;
+            }
+            if base_alpha {
+                p_base[channels] = (a_out * 255.0).round() as u8;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Repeats `input` horizontally and vertically to fill an image of size
+/// `out_width x out_height`, starting from the upper left corner
+pub fn tile<T: Number>(input: &Image<T>, out_width: u32, out_height: u32) -> ImgProcResult<Image<T>> {
+    error::check_non_empty(input)?;
+
+    let (width, height, channels, alpha) = input.info().whca();
+    let mut data = Vec::with_capacity((out_width * out_height * channels as u32) as usize);
+
+    for y in 0..out_height {
+        for x in 0..out_width {
+            data.extend_from_slice(input.get_pixel_unchecked(x % width, y % height));
+        }
+    }
+
+    Ok(Image::from_vec(out_width, out_height, channels, alpha, data))
+}
+
+/// Arranges `images` row by row into a grid with `cols` columns, with `padding` pixels between
+/// neighboring cells. Every cell is the size of the largest image, each image is placed in the
+/// upper left corner of its cell, and the remaining space is filled with `bg`. If there are fewer
+/// images than `cols`, the grid has one column per image. All images must have the same number of
+/// channels as `bg`
+pub fn montage(images: &[Image<u8>], cols: u32, padding: u32, bg: &[u8]) -> ImgProcResult<Image<u8>> {
+    error::check_non_empty_slice(images, "images")?;
+    error::check_positive(cols, "cols")?;
+
+    let (channels, alpha) = (images[0].info().channels, images[0].info().alpha);
+    error::check_equal(channels as usize, bg.len(), "background length")?;
+    for img in images.iter() {
+        error::check_equal(channels, img.info().channels, "image channels")?;
+    }
+
+    let cell_width = images.iter().map(|img| img.info().width).max().unwrap();
+    let cell_height = images.iter().map(|img| img.info().height).max().unwrap();
+    let cols = cols.min(images.len() as u32);
+    let rows = (images.len() as u32).div_ceil(cols);
+
+    let width = cols * cell_width + (cols - 1) * padding;
+    let height = rows * cell_height + (rows - 1) * padding;
+    let mut output = Image::from_vec(width, height, channels, alpha, bg.repeat((width * height) as usize));
+
+    for (i, img) in images.iter().enumerate() {
+        let x = (i as u32 % cols) * (cell_width + padding);
+        let y = (i as u32 / cols) * (cell_height + padding);
+
+        for j in 0..img.info().height {
+            for k in 0..img.info().width {
+                output.set_pixel(x + k, y + j, img.get_pixel_unchecked(k, j));
+            }
+        }
+    }
+
+    Ok(output)
+}
+
 ////////////////////////////
 // Affine transformations
 ////////////////////////////
 
 /// Scales an image horizontally by `x_factor` and vertically by `y_factor` using the specified
-/// `method`
+/// `method`. `Scale::Triangle` and `Scale::CatmullRom` are resampled with
+/// [`resize`](fn.resize.html)
 #[cfg(not(feature = "rayon"))]
 pub fn scale(input: &Image<f64>, x_factor: f64, y_factor: f64, method: Scale) -> ImgProcResult<Image<f64>> {
     error::check_non_neg(x_factor, "x_factor")?;
@@ -130,13 +287,17 @@ pub fn scale(input: &Image<f64>, x_factor: f64, y_factor: f64, method: Scale) ->
         },
         Scale::Lanczos => {
             scale_lanczos_resampling(input, &mut output, x_factor, y_factor, 3);
+        },
+        Scale::Triangle | Scale::CatmullRom => {
+            output = resize(input, width, height, method)?;
         }
     }
 
     Ok(output)
 }
 /// Scales an image horizontally by `x_factor` and vertically by `y_factor` using the specified
-/// `method`
+/// `method`. `Scale::Triangle` and `Scale::CatmullRom` are resampled with
+/// [`resize`](fn.resize.html)
 #[cfg(feature = "rayon")]
 pub fn scale(input: &Image<f64>, x_factor: f64, y_factor: f64, method: Scale) -> ImgProcResult<Image<f64>> {
     error::check_non_neg(x_factor, "x_factor")?;
@@ -158,6 +319,9 @@ pub fn scale(input: &Image<f64>, x_factor: f64, y_factor: f64, method: Scale) ->
         },
         Scale::Lanczos => {
             Ok(scale_lanczos_resampling(input, &info, x_factor, y_factor, 3))
+        },
+        Scale::Triangle | Scale::CatmullRom => {
+            resize(input, width, height, method)
         }
     }
 }
@@ -192,6 +356,248 @@ pub fn scale_lanczos(input: &Image<f64>, x_factor: f64, y_factor: f64, size: u32
     Ok(scale_lanczos_resampling(input, &info, x_factor, y_factor, size))
 }
 
+/// Resizes an image to `width` x `height` using the specified `method`. Pixel centers of the
+/// input and output images are aligned, and the image is resampled horizontally and then
+/// vertically. When downscaling, the kernel is widened by the scale factor so that every input
+/// pixel contributes to the output
+pub fn resize(input: &Image<f64>, width: u32, height: u32, method: Scale) -> ImgProcResult<Image<f64>> {
+    check_resize(input, width, height)?;
+
+    Ok(resize_channels(input, width, height, &method))
+}
+
+/// Resizes an image to `width` x `height` using the specified `method`, as in
+/// [`resize`](fn.resize.html). If `input` has an alpha channel, the color channels are
+/// premultiplied by alpha while resampling, so that the colors of transparent pixels do not bleed
+/// into their neighbors
+pub fn resize_premultiplied(input: &Image<f64>, width: u32, height: u32, method: Scale) -> ImgProcResult<Image<f64>> {
+    check_resize(input, width, height)?;
+
+    util::apply_premultiplied(input, |img| Ok(resize_channels(img, width, height, &method)))
+}
+
+/// Resizes an sRGB image to `width` x `height` in linear light using the specified `method`.
+/// Resampling gamma-encoded values darkens high-contrast detail when downscaling; this decodes
+/// `input` with the sRGB transfer function, resizes, and then re-encodes the result
+pub fn resize_linear(input: &Image<u8>, width: u32, height: u32, method: Scale) -> ImgProcResult<Image<u8>> {
+    let linear = colorspace::srgb_to_linear(input);
+    let resized = resize(&linear, width, height, method)?;
+
+    Ok(colorspace::linear_to_srgb(&resized))
+}
+
+/// Resizes an image by a factor of `scale` in both dimensions using the specified `method`. The
+/// output dimensions are rounded to the nearest integer, with a minimum of 1 pixel each. See
+/// [`resize`](fn.resize.html)
+///
+/// # Arguments
+///
+/// * `scale` - Must be positive
+pub fn resize_by(input: &Image<u8>, scale: f64, method: Scale) -> ImgProcResult<Image<u8>> {
+    error::check_positive(scale, "scale")?;
+
+    let (width, height) = input.info().wh();
+    let width_out = ((width as f64 * scale).round() as u32).max(1);
+    let height_out = ((height as f64 * scale).round() as u32).max(1);
+
+    if (width_out, height_out) == (width, height) {
+        return Ok(input.clone());
+    }
+
+    Ok(resize(&input.clone().into(), width_out, height_out, method)?.into())
+}
+
+/// Resizes an image by the fraction `num / den` in both dimensions using the specified `method`.
+/// Each output dimension is `floor(dim * num / den)`, computed with integer arithmetic, with a
+/// minimum of 1 pixel. Because of the rounding, the aspect ratio is only approximately preserved;
+/// for example, a 1200x800 image resized by 2/3 is 800x533. See [`resize`](fn.resize.html)
+///
+/// # Arguments
+///
+/// * `num` - Must be positive
+/// * `den` - Must be positive
+pub fn resize_fraction(input: &Image<u8>, num: u32, den: u32, method: Scale) -> ImgProcResult<Image<u8>> {
+    error::check_positive(num, "num")?;
+    error::check_positive(den, "den")?;
+
+    let (width, height) = input.info().wh();
+    let scale = |len: u32| ((len as u64 * num as u64 / den as u64).clamp(1, u32::MAX as u64)) as u32;
+    let (width_out, height_out) = (scale(width), scale(height));
+
+    if (width_out, height_out) == (width, height) {
+        return Ok(input.clone());
+    }
+
+    Ok(resize(&input.clone().into(), width_out, height_out, method)?.into())
+}
+
+/// Resizes an image with bilinear interpolation to the largest size that fits inside
+/// `target_width x target_height` while preserving its aspect ratio, then centers it on an image
+/// of exactly `target_width x target_height` filled with `fill`
+pub fn letterbox(input: &Image<u8>, target_width: u32, target_height: u32, fill: &[u8]) -> ImgProcResult<Image<u8>> {
+    error::check_non_empty(input)?;
+    error::check_equal(input.info().channels as usize, fill.len(), "fill length")?;
+    error::check_positive(target_width, "target_width")?;
+    error::check_positive(target_height, "target_height")?;
+
+    let (width, height, channels, alpha) = input.info().whca();
+    let factor = (target_width as f64 / width as f64).min(target_height as f64 / height as f64);
+    let scaled_width = ((width as f64 * factor).round() as u32).clamp(1, target_width);
+    let scaled_height = ((height as f64 * factor).round() as u32).clamp(1, target_height);
+    let scaled: Image<u8> = resize(&input.clone().into(), scaled_width, scaled_height, Scale::Bilinear)?.into();
+
+    let mut output = Image::from_vec(target_width, target_height, channels, alpha,
+                                     fill.repeat((target_width * target_height) as usize));
+    let x = (target_width - scaled_width) / 2;
+    let y = (target_height - scaled_height) / 2;
+
+    for j in 0..scaled_height {
+        for i in 0..scaled_width {
+            output.set_pixel(x + i, y + j, scaled.get_pixel_unchecked(i, j));
+        }
+    }
+
+    Ok(output)
+}
+
+/// Changes the dimensions of an image to `width x height` without resampling it. The image is
+/// positioned on the new canvas according to `anchor`; pixels that fall outside the canvas are
+/// cropped and new area is filled with `fill`
+pub fn resize_canvas(input: &Image<u8>, width: u32, height: u32, anchor: Anchor, fill: &[u8]) -> ImgProcResult<Image<u8>> {
+    error::check_equal(input.info().channels as usize, fill.len(), "fill length")?;
+    error::check_positive(width, "width")?;
+    error::check_positive(height, "height")?;
+
+    let (width_in, height_in, channels, alpha) = input.info().whca();
+
+    // Position of each axis of the input relative to the canvas: 0 for the start, 1 for the
+    // middle, and 2 for the end
+    let (pos_x, pos_y) = match anchor {
+        Anchor::TopLeft => (0, 0),
+        Anchor::Top => (1, 0),
+        Anchor::TopRight => (2, 0),
+        Anchor::Left => (0, 1),
+        Anchor::Center => (1, 1),
+        Anchor::Right => (2, 1),
+        Anchor::BottomLeft => (0, 2),
+        Anchor::Bottom => (1, 2),
+        Anchor::BottomRight => (2, 2),
+    };
+    let offset_x = ((width as i64 - width_in as i64) * pos_x).div_euclid(2);
+    let offset_y = ((height as i64 - height_in as i64) * pos_y).div_euclid(2);
+
+    let mut output = Image::from_vec(width, height, channels, alpha, fill.repeat((width * height) as usize));
+
+    for y in 0..height {
+        let y_in = y as i64 - offset_y;
+        if y_in < 0 || y_in >= height_in as i64 {
+            continue;
+        }
+
+        for x in 0..width {
+            let x_in = x as i64 - offset_x;
+            if x_in >= 0 && x_in < width_in as i64 {
+                output.set_pixel(x, y, input.get_pixel_unchecked(x_in as u32, y_in as u32));
+            }
+        }
+    }
+
+    Ok(output)
+}
+
+/// Shrinks an image by an integer factor by applying a Gaussian blur with standard deviation
+/// `factor / 2` and then keeping every `factor`th pixel in each dimension, starting from the upper
+/// left corner. The blur removes detail that would otherwise alias. The output image is
+/// `ceil(width / factor) x ceil(height / factor)`
+pub fn downsample(input: &Image<u8>, factor: u32) -> ImgProcResult<Image<u8>> {
+    error::check_positive(factor, "factor")?;
+    if factor == 1 {
+        return Ok(input.clone());
+    }
+
+    let sigma = factor as f64 / 2.0;
+    let size = 2 * (3.0 * sigma).ceil() as u32 + 1;
+    let blurred: Image<u8> = filter::gaussian_blur(&input.clone().into(), size, sigma)?.into();
+
+    let (width, height, channels, alpha) = input.info().whca();
+    let (width_out, height_out) = (width.div_ceil(factor), height.div_ceil(factor));
+    let mut data = Vec::with_capacity((width_out * height_out * channels as u32) as usize);
+
+    for y in 0..height_out {
+        for x in 0..width_out {
+            data.extend_from_slice(blurred.get_pixel_unchecked(x * factor, y * factor));
+        }
+    }
+
+    Ok(Image::from_vec(width_out, height_out, channels, alpha, data))
+}
+
+/// Enlarges an image by an integer factor by replicating each pixel into a `factor x factor`
+/// block, without any interpolation
+pub fn scale_integer<T: Number>(input: &Image<T>, factor: u32) -> ImgProcResult<Image<T>> {
+    error::check_positive(factor, "factor")?;
+
+    let (width, height, channels, alpha) = input.info().whca();
+    let mut data = Vec::with_capacity((width * height * factor * factor * channels as u32) as usize);
+
+    for y in 0..(height * factor) {
+        for x in 0..(width * factor) {
+            data.extend_from_slice(input.get_pixel_unchecked(x / factor, y / factor));
+        }
+    }
+
+    Ok(Image::from_vec(width * factor, height * factor, channels, alpha, data))
+}
+
+/// Doubles the size of an image using the Scale2x (EPX) algorithm, which enlarges each pixel
+/// into a 2x2 block and fills in the corners of the block from matching neighbors so that
+/// diagonal edges are smoothed rather than turned into stair steps. Pixels outside the image are
+/// treated as copies of the nearest edge pixel
+pub fn scale2x<T: Number>(input: &Image<T>) -> ImgProcResult<Image<T>> {
+    let (width, height, channels, alpha) = input.info().whca();
+    let mut output = Image::blank(ImageInfo::new(width * 2, height * 2, channels, alpha));
+
+    for y in 0..height {
+        for x in 0..width {
+            let p = input.get_pixel_unchecked(x, y);
+            let up = input.get_pixel_unchecked(x, y.saturating_sub(1));
+            let left = input.get_pixel_unchecked(x.saturating_sub(1), y);
+            let right = input.get_pixel_unchecked((x + 1).min(width - 1), y);
+            let down = input.get_pixel_unchecked(x, (y + 1).min(height - 1));
+
+            let upper_left = if left == up && left != down && up != right { up } else { p };
+            let upper_right = if up == right && up != left && right != down { right } else { p };
+            let lower_left = if down == left && down != right && left != up { left } else { p };
+            let lower_right = if right == down && right != up && down != left { down } else { p };
+
+            output.set_pixel(2 * x, 2 * y, upper_left);
+            output.set_pixel(2 * x + 1, 2 * y, upper_right);
+            output.set_pixel(2 * x, 2 * y + 1, lower_left);
+            output.set_pixel(2 * x + 1, 2 * y + 1, lower_right);
+        }
+    }
+
+    Ok(output)
+}
+
+/// Swaps the rows and columns of an image, so that pixel `(x, y)` of the output is pixel `(y, x)`
+/// of `input`. The output image is `height x width`
+pub fn transpose<T: Number>(input: &Image<T>) -> Image<T> {
+    let (width, height, channels, alpha) = input.info().whca();
+    let (width, height, channels_usize) = (width as usize, height as usize, channels as usize);
+    let data_in = input.data();
+    let mut data = Vec::with_capacity(data_in.len());
+
+    for x in 0..width {
+        for y in 0..height {
+            let start = (y * width + x) * channels_usize;
+            data.extend_from_slice(&data_in[start..(start + channels_usize)]);
+        }
+    }
+
+    Image::from_vec(height as u32, width as u32, channels, alpha, data)
+}
+
 /// Translates an image to the position with upper left corner located at `(x, y)`. Fills in the
 /// rest of the image as black
 pub fn translate<T: Number>(input: &Image<T>, x: u32, y: u32) -> ImgProcResult<Image<T>> {
@@ -260,6 +666,63 @@ pub fn rotate(input: &Image<f64>, degrees: f64) -> ImgProcResult<Image<f64>> {
 
 /// Reflects an image across the specified axis
 pub fn reflect<T: Number>(input: &Image<T>, axis: Refl) -> ImgProcResult<Image<T>> {
+    Ok(flip(input, axis))
+}
+
+/// Losslessly transforms an image stored with the EXIF orientation `orientation` so that it is
+/// upright, using only flips and transposition
+pub fn apply_orientation<T: Number>(input: &Image<T>, orientation: Orientation) -> Image<T> {
+    match orientation {
+        Orientation::Normal => input.clone(),
+        Orientation::FlipHorizontal => flip(input, Refl::Vertical),
+        Orientation::Rotate180 => flip(&flip(input, Refl::Vertical), Refl::Horizontal),
+        Orientation::FlipVertical => flip(input, Refl::Horizontal),
+        Orientation::Transpose => transpose(input),
+        Orientation::Rotate90 => flip(&transpose(input), Refl::Vertical),
+        Orientation::Transverse => transpose(&flip(&flip(input, Refl::Vertical), Refl::Horizontal)),
+        Orientation::Rotate270 => flip(&transpose(input), Refl::Horizontal),
+    }
+}
+
+/// Guesses the orientation of an image from its content, returning the orientation to pass to
+/// `apply_orientation` to make it upright
+///
+/// Photographs and scanned text are dominated by horizontal structure (horizons, text lines),
+/// which produces stronger vertical gradients than horizontal ones. If the horizontal gradients
+/// dominate instead, the image is assumed to be lying on its side and `Orientation::Rotate90` is
+/// returned; otherwise `Orientation::Normal` is returned. Gradient direction alone cannot tell a
+/// quarter turn clockwise from one counterclockwise, or an upright image from an upside-down one,
+/// so no other orientations are suggested.
+pub fn suggest_orientation(input: &Image<u8>) -> Orientation {
+    // There are no interior pixels to measure gradients at
+    let (width, height) = input.info().wh();
+    if width < 3 || height < 3 {
+        return Orientation::Normal;
+    }
+
+    let gray = analysis::to_grayscale(input);
+
+    let mut horz = 0u64;
+    let mut vert = 0u64;
+    for y in 1..(height - 1) {
+        for x in 1..(width - 1) {
+            horz += (gray.get_pixel(x + 1, y)[0] as i32 - gray.get_pixel(x - 1, y)[0] as i32)
+                .unsigned_abs() as u64;
+            vert += (gray.get_pixel(x, y + 1)[0] as i32 - gray.get_pixel(x, y - 1)[0] as i32)
+                .unsigned_abs() as u64;
+        }
+    }
+
+    // Require a clear margin so that images without a dominant direction are left alone
+    if horz > 2 * vert {
+        Orientation::Rotate90
+    } else {
+        Orientation::Normal
+    }
+}
+
+// Reflects `input` across the specified axis
+fn flip<T: Number>(input: &Image<T>, axis: Refl) -> Image<T> {
     let mut output = Image::blank(input.info());
     let (width, height) = output.info().wh();
 
@@ -280,7 +743,7 @@ pub fn reflect<T: Number>(input: &Image<T>, axis: Refl) -> ImgProcResult<Image<T
         },
     }
 
-    Ok(output)
+    output
 }
 
 /// Shears an image
@@ -317,6 +780,178 @@ pub fn shear(input: &Image<f64>, shear_x: f64, shear_y: f64) -> ImgProcResult<Im
     Ok(output)
 }
 
+/////////////////////
+// Geometric warps
+/////////////////////
+
+/// Maps an image from Cartesian coordinates to polar coordinates around `center` using bilinear
+/// sampling. Row `y` of the output holds the pixels at radius `y` from `center`, and column `x`
+/// holds the pixels at angle `2 * PI * x / width` counterclockwise from the positive x axis (with
+/// the y axis pointing down). The output has one row for each radius up to the distance to the
+/// farthest corner, and one column per pixel of circumference at that radius. Samples that fall
+/// outside of `input` are black
+pub fn to_polar(input: &Image<u8>, center: (u32, u32)) -> ImgProcResult<Image<u8>> {
+    error::check_non_empty(input)?;
+    check_center(center, input.info().wh())?;
+
+    let (width, height, channels, alpha) = input.info().whca();
+    let (c_x, c_y) = (center.0 as f64, center.1 as f64);
+    let max_radius = [(0.0, 0.0), (width as f64 - 1.0, 0.0), (0.0, height as f64 - 1.0),
+                      (width as f64 - 1.0, height as f64 - 1.0)]
+        .iter()
+        .map(|(x, y)| (x - c_x).hypot(y - c_y))
+        .fold(0.0, f64::max);
+    let width_out = ((2.0 * PI * max_radius).ceil() as u32).max(1);
+    let height_out = max_radius.ceil() as u32 + 1;
+
+    let input: Image<f64> = input.clone().into();
+    let mut output = Image::blank(ImageInfo::new(width_out, height_out, channels, alpha));
+
+    for y in 0..height_out {
+        for x in 0..width_out {
+            let (sin, cos) = (2.0 * PI * x as f64 / width_out as f64).sin_cos();
+            let x_in = c_x + y as f64 * cos;
+            let y_in = c_y - y as f64 * sin;
+
+            if x_in >= 0.0 && x_in <= (width - 1) as f64 && y_in >= 0.0 && y_in <= (height - 1) as f64 {
+                output.set_pixel(x, y, &sample_bilinear(&input, x_in, y_in, false));
+            }
+        }
+    }
+
+    Ok(output.into())
+}
+
+/// Maps an image from polar coordinates, as produced by `to_polar`, back to a `width x height`
+/// image in Cartesian coordinates around `center` using bilinear sampling. Pixels farther from
+/// `center` than the largest radius in `input` are black
+pub fn from_polar(input: &Image<u8>, center: (u32, u32), width: u32, height: u32) -> ImgProcResult<Image<u8>> {
+    error::check_non_empty(input)?;
+    check_center(center, (width, height))?;
+
+    let (width_in, height_in, channels, alpha) = input.info().whca();
+    let (c_x, c_y) = (center.0 as f64, center.1 as f64);
+
+    let input: Image<f64> = input.clone().into();
+    let mut output = Image::blank(ImageInfo::new(width, height, channels, alpha));
+
+    for y in 0..height {
+        for x in 0..width {
+            let (d_x, d_y) = (x as f64 - c_x, c_y - y as f64);
+            let radius = d_x.hypot(d_y);
+            let angle = d_y.atan2(d_x).rem_euclid(2.0 * PI);
+
+            if radius <= (height_in - 1) as f64 {
+                let x_in = angle / (2.0 * PI) * width_in as f64;
+                output.set_pixel(x, y, &sample_bilinear(&input, x_in, radius, true));
+            }
+        }
+    }
+
+    Ok(output.into())
+}
+
+/// Applies the perspective transformation described by the 3x3 row-major matrix `homography` to
+/// an image, producing a `width x height` output. Each output pixel `(x, y)` is sampled from
+/// `input` at the point that `homography` maps to `(x, y)` in homogeneous coordinates, using the
+/// interpolation specified by `method`. Output pixels that map to points outside of `input` are
+/// black. `homography` must be invertible
+pub fn warp_perspective(input: &Image<u8>, homography: &[f64; 9], width: u32, height: u32, method: Scale) -> ImgProcResult<Image<u8>> {
+    error::check_non_empty(input)?;
+    let inverse = invert_3x3(homography)
+        .ok_or_else(|| ImgProcError::InvalidArgError("homography is not invertible".to_string()))?;
+
+    let (width_in, height_in, channels, alpha) = input.info().whca();
+    let input: Image<f64> = input.clone().into();
+    let mut output = Image::blank(ImageInfo::new(width, height, channels, alpha));
+
+    for y in 0..height {
+        for x in 0..width {
+            let coords = util::vector_mul(&inverse, &[x as f64, y as f64, 1.0])?;
+            if coords[2].abs() <= f64::EPSILON {
+                continue;
+            }
+
+            let x_in = coords[0] / coords[2];
+            let y_in = coords[1] / coords[2];
+            if x_in >= -0.5 && x_in < width_in as f64 - 0.5 && y_in >= -0.5 && y_in < height_in as f64 - 0.5 {
+                output.set_pixel(x, y, &sample_kernel(&input, x_in, y_in, &method));
+            }
+        }
+    }
+
+    Ok(output.into())
+}
+
+/// Computes the 3x3 row-major homography that maps each point in `src` to the corresponding point
+/// in `dst`, normalized so that its last element is 1. No three points in `src` can be collinear
+pub fn homography_from_points(src: [(f64, f64); 4], dst: [(f64, f64); 4]) -> ImgProcResult<[f64; 9]> {
+    let scale = src.iter().fold(1.0, |max: f64, (x, y)| max.max(x.abs()).max(y.abs()));
+    for i in 0..4 {
+        let (a, b, c) = (src[i], src[(i + 1) % 4], src[(i + 2) % 4]);
+        let area = (b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0);
+
+        if area.abs() <= 1e-10 * scale * scale {
+            return Err(ImgProcError::InvalidArgError("three of the source points are collinear".to_string()));
+        }
+    }
+
+    // Each correspondence (x, y) -> (u, v) contributes two rows of the linear system in the first
+    // eight elements of the homography
+    let mut mat = Vec::with_capacity(64);
+    let mut vec = Vec::with_capacity(8);
+    for ((x, y), (u, v)) in src.iter().zip(dst.iter()) {
+        mat.extend_from_slice(&[*x, *y, 1.0, 0.0, 0.0, 0.0, -u * x, -u * y]);
+        mat.extend_from_slice(&[0.0, 0.0, 0.0, *x, *y, 1.0, -v * x, -v * y]);
+        vec.push(*u);
+        vec.push(*v);
+    }
+
+    let solution = Matrix::new(8, 8, mat).solve(Vector::new(vec))?;
+
+    let mut homography = [1.0; 9];
+    homography[..8].copy_from_slice(solution.data());
+
+    Ok(homography)
+}
+
+// Returns the inverse of the 3x3 row-major matrix `mat`, or `None` if it is singular
+fn invert_3x3(mat: &[f64; 9]) -> Option<[f64; 9]> {
+    let cofactor = |r_0: usize, r_1: usize, c_0: usize, c_1: usize| {
+        mat[r_0 * 3 + c_0] * mat[r_1 * 3 + c_1] - mat[r_0 * 3 + c_1] * mat[r_1 * 3 + c_0]
+    };
+
+    // Transposed matrix of cofactors
+    let adjugate = [
+        cofactor(1, 2, 1, 2), -cofactor(0, 2, 1, 2), cofactor(0, 1, 1, 2),
+        -cofactor(1, 2, 0, 2), cofactor(0, 2, 0, 2), -cofactor(0, 1, 0, 2),
+        cofactor(1, 2, 0, 1), -cofactor(0, 2, 0, 1), cofactor(0, 1, 0, 1),
+    ];
+    let det = mat[0] * adjugate[0] + mat[1] * adjugate[3] + mat[2] * adjugate[6];
+
+    let scale = mat.iter().fold(0.0, |max: f64, val| max.max(val.abs()));
+    if !det.is_finite() || det.abs() <= f64::EPSILON * scale.powi(3) {
+        return None;
+    }
+
+    let mut inverse = [0.0; 9];
+    for (inv, adj) in inverse.iter_mut().zip(adjugate.iter()) {
+        *inv = adj / det;
+    }
+
+    Some(inverse)
+}
+
+// Checks that `center` lies within an image of dimensions `dimensions`
+fn check_center(center: (u32, u32), dimensions: (u32, u32)) -> ImgProcResult<()> {
+    if center.0 >= dimensions.0 || center.1 >= dimensions.1 {
+        return Err(ImgProcError::InvalidArgError(format!("invalid center: ({}, {}) is outside of \
+            a {}x{} image", center.0, center.1, dimensions.0, dimensions.1)));
+    }
+
+    Ok(())
+}
+
 ///////////////////////
 // Scaling Algorithms
 ///////////////////////
@@ -423,7 +1058,107 @@ fn scale_lanczos_resampling(input: &Image<f64>, info: &ImageInfo, x_factor: f64,
         .collect();
 
     Image::from_vec_of_vec(width, height, channels, info.alpha, data)
+}
+
+// Checks that `input` is not empty and that the output dimensions are positive
+fn check_resize(input: &Image<f64>, width: u32, height: u32) -> ImgProcResult<()> {
+    error::check_positive(width, "width")?;
+    error::check_positive(height, "height")?;
+    error::check_non_empty(input)
+}
+
+// Resizes every channel of `input` to `width` x `height`
+fn resize_channels(input: &Image<f64>, width: u32, height: u32, method: &Scale) -> Image<f64> {
+    let (w_in, h_in, channels, alpha) = input.info().whca();
+    let horz_weights = resize_weights(w_in, width, method);
+    let vert_weights = resize_weights(h_in, height, method);
+
+    let mut horz = Image::blank(ImageInfo::new(width, h_in, channels, alpha));
+    let mut p_out = vec![0.0; channels as usize];
+    for y in 0..h_in {
+        for (x, weights) in horz_weights.iter().enumerate() {
+            resample_pixel(&mut p_out, weights, |i| input.get_pixel(i, y));
+            horz.set_pixel(x as u32, y, &p_out);
+        }
+    }
 
+    let mut output = Image::blank(ImageInfo::new(width, height, channels, alpha));
+    for (y, weights) in vert_weights.iter().enumerate() {
+        for x in 0..width {
+            resample_pixel(&mut p_out, weights, |i| horz.get_pixel(x, i));
+            output.set_pixel(x, y as u32, &p_out);
+        }
+    }
+
+    output
+}
+
+// Computes the input indices and weights contributing to each output index along one axis
+fn resize_weights(len_in: u32, len_out: u32, method: &Scale) -> Vec<Vec<(u32, f64)>> {
+    let ratio = len_in as f64 / len_out as f64;
+    let max = len_in as i64 - 1;
+    let mut weights = Vec::with_capacity(len_out as usize);
+
+    // When downscaling, the kernel is stretched to cover every input pixel
+    let filter_scale = ratio.max(1.0);
+
+    for i in 0..len_out {
+        let center = (i as f64 + 0.5) * ratio - 0.5;
+
+        let support = filter_scale * match method {
+            Scale::NearestNeighbor => {
+                let index = (((i as f64 + 0.5) * ratio).floor() as i64).clamp(0, max);
+                weights.push(vec![(index as u32, 1.0)]);
+                continue;
+            },
+            Scale::Bilinear | Scale::Triangle => 1.0,
+            Scale::Bicubic | Scale::CatmullRom => 2.0,
+            Scale::Lanczos => 3.0,
+        };
+
+        let mut w = Vec::new();
+        let mut sum = 0.0;
+        for j in ((center - support).ceil() as i64)..=((center + support).floor() as i64) {
+            let weight = resize_kernel(method, (j as f64 - center) / filter_scale) / filter_scale;
+            if weight != 0.0 {
+                w.push((j.clamp(0, max) as u32, weight));
+                sum += weight;
+            }
+        }
+
+        for (_, weight) in w.iter_mut() {
+            *weight /= sum;
+        }
+        weights.push(w);
+    }
+
+    weights
+}
+
+// The 1D resampling kernel corresponding to `method`
+fn resize_kernel(method: &Scale, x: f64) -> f64 {
+    match method {
+        Scale::NearestNeighbor => if x.abs() < 0.5 { 1.0 } else { 0.0 },
+        Scale::Bilinear => util::clamp_zero(1.0 - x.abs()),
+        Scale::Triangle => util::triangle_fn(x),
+        Scale::Bicubic => util::cubic_weighting_fn(x),
+        Scale::CatmullRom => util::catmull_rom_fn(x),
+        Scale::Lanczos => util::lanczos_kernel(x, 3.0),
+    }
+}
+
+fn resample_pixel<'a, F>(output: &mut [f64], weights: &[(u32, f64)], get_pixel: F)
+    where F: Fn(u32) -> &'a [f64] {
+    for val in output.iter_mut() {
+        *val = 0.0;
+    }
+
+    for (index, weight) in weights.iter() {
+        let p_in = get_pixel(*index);
+        for (c, val) in output.iter_mut().enumerate() {
+            *val += p_in[c] * weight;
+        }
+    }
 }
 
 fn interpolate_nearest_neighbor(input: &Image<f64>, x_factor: f64, y_factor: f64, x: u32, y: u32) -> &[f64] {
@@ -507,4 +1242,81 @@ fn interpolate_lanczos(input: &Image<f64>, x_factor: f64, y_factor: f64, size: u
     }
 
     p_out
-}
\ No newline at end of file
+}
+
+// Samples `input` at `(x, y)` using bilinear interpolation. Coordinates beyond the last row or
+// column are clamped to the edge of the image, or wrap around to the first column if `wrap_x` is
+// `true`
+fn sample_bilinear(input: &Image<f64>, x: f64, y: f64, wrap_x: bool) -> Vec<f64> {
+    let (width, height) = input.info().wh();
+    let x = if wrap_x { x.rem_euclid(width as f64) } else { x.clamp(0.0, (width - 1) as f64) };
+    let y = y.clamp(0.0, (height - 1) as f64);
+    let (x_0, y_0) = ((x.floor() as u32).min(width - 1), y.floor() as u32);
+    let x_1 = if wrap_x { (x_0 + 1) % width } else { (x_0 + 1).min(width - 1) };
+    let y_1 = (y_0 + 1).min(height - 1);
+    let (d_x, d_y) = (x - x_0 as f64, y - y_0 as f64);
+
+    let p_00 = input.get_pixel_unchecked(x_0, y_0);
+    let p_10 = input.get_pixel_unchecked(x_1, y_0);
+    let p_01 = input.get_pixel_unchecked(x_0, y_1);
+    let p_11 = input.get_pixel_unchecked(x_1, y_1);
+
+    (0..(input.info().channels as usize))
+        .map(|c| (p_00[c] * (1.0 - d_x) + p_10[c] * d_x) * (1.0 - d_y)
+            + (p_01[c] * (1.0 - d_x) + p_11[c] * d_x) * d_y)
+        .collect()
+}
+
+// Samples `input` at `(x, y)` using the resampling kernel corresponding to `method`. Pixels outside
+// of the image are clamped to the nearest edge pixel
+fn sample_kernel(input: &Image<f64>, x: f64, y: f64, method: &Scale) -> Vec<f64> {
+    let (width, height) = input.info().wh();
+
+    if let Scale::NearestNeighbor = method {
+        let x_in = (x.round() as i64).clamp(0, width as i64 - 1) as u32;
+        let y_in = (y.round() as i64).clamp(0, height as i64 - 1) as u32;
+        return input.get_pixel_unchecked(x_in, y_in).to_vec();
+    }
+
+    let horz_weights = sample_weights(x, width, method);
+    let vert_weights = sample_weights(y, height, method);
+
+    let mut p_out = vec![0.0; input.info().channels as usize];
+    for (j, w_y) in vert_weights.iter() {
+        for (i, w_x) in horz_weights.iter() {
+            let p_in = input.get_pixel_unchecked(*i, *j);
+            for (c, val) in p_out.iter_mut().enumerate() {
+                *val += p_in[c] * w_x * w_y;
+            }
+        }
+    }
+
+    p_out
+}
+
+// Computes the normalized weights of the indices along one axis of length `len` contributing to a
+// sample at `center`
+fn sample_weights(center: f64, len: u32, method: &Scale) -> Vec<(u32, f64)> {
+    let support = match method {
+        Scale::NearestNeighbor => 0.5,
+        Scale::Bilinear | Scale::Triangle => 1.0,
+        Scale::Bicubic | Scale::CatmullRom => 2.0,
+        Scale::Lanczos => 3.0,
+    };
+
+    let mut weights = Vec::new();
+    let mut sum = 0.0;
+    for j in ((center - support).ceil() as i64)..=((center + support).floor() as i64) {
+        let weight = resize_kernel(method, j as f64 - center);
+        if weight != 0.0 {
+            weights.push((j.clamp(0, len as i64 - 1) as u32, weight));
+            sum += weight;
+        }
+    }
+
+    for (_, weight) in weights.iter_mut() {
+        *weight /= sum;
+    }
+
+    weights
+}
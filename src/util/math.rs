@@ -1,16 +1,14 @@
 use crate::error;
 use crate::image::{Number, SubImage, BaseImage};
-use crate::error::ImgProcResult;
+use crate::error::{ImgProcResult, ImgProcError};
+use crate::util::constants::{K_LAPLACIAN, K_SHARPEN, K_SOBEL_1D_HORZ, K_SOBEL_1D_VERT};
 
 use std::f64::consts::{PI, E};
-use rulinalg::matrix::{Matrix, BaseMatrix};
 
 /// Returns the result of the multiplication of a square matrix by a vector
 pub fn vector_mul<T: Number>(mat: &[T], input: &[T]) -> ImgProcResult<Vec<T>> {
     let rows = input.len();
-    let mat_cols = mat.len() / rows;
-
-    error::check_equal(mat_cols, rows, "mat and vec dimensions")?;
+    check_mat_vec(mat.len(), rows)?;
 
     let mut output = vec![0.into(); rows];
 
@@ -26,9 +24,7 @@ pub fn vector_mul<T: Number>(mat: &[T], input: &[T]) -> ImgProcResult<Vec<T>> {
 /// Returns the result of the multiplication of a square matrix by a vector in `output`
 pub fn vector_mul_mut<T: Number>(mat: &[T], input: &[T], output: &mut Vec<T>) -> ImgProcResult<()> {
     let rows = input.len();
-    let mat_cols = mat.len() / rows;
-
-    error::check_equal(mat_cols, rows, "mat and vec dimensions")?;
+    check_mat_vec(mat.len(), rows)?;
 
     output.clear();
     for _ in 0..rows {
@@ -44,28 +40,60 @@ pub fn vector_mul_mut<T: Number>(mat: &[T], input: &[T], output: &mut Vec<T>) ->
     Ok(())
 }
 
+// Checks that a matrix of length `mat_len` is a square matrix with `rows` rows
+fn check_mat_vec(mat_len: usize, rows: usize) -> ImgProcResult<()> {
+    if rows == 0 {
+        return Err(ImgProcError::InvalidArgError("vec must not be empty".to_string()));
+    } else if mat_len % rows != 0 {
+        return Err(ImgProcError::InvalidArgError(format!("invalid mat and vec dimensions: mat \
+            length {} is not a multiple of vec length {}", mat_len, rows)));
+    }
+
+    error::check_equal(mat_len / rows, rows, "mat and vec dimensions")
+}
+
 /// If `kernel` is separable, returns the (vertical kernel, horizontal kernel); otherwise returns None
-pub fn separate_kernel(kernel: &[f64]) -> Option<(Vec<f64>, Vec<f64>)> {
+///
+/// A kernel is separable if it has rank one. The factors are taken from the row and column of
+/// the largest magnitude element of `kernel`, and the kernel is considered separable if their
+/// outer product reproduces `kernel` to within a small tolerance
+pub fn separate_kernel(kernel: &[f64]) -> ImgProcResult<Option<(Vec<f64>, Vec<f64>)>> {
+    error::check_positive(kernel.len(), "kernel length")?;
+    error::check_square(kernel.len() as f64, "kernel length")?;
+
     let size = (kernel.len() as f32).sqrt() as usize;
-    let kernel_mat = Matrix::new(size, size, kernel);
-    let size = kernel_mat.cols();
-    let (s, u, v) = kernel_mat.svd().unwrap();
+    let mut pivot = 0;
+    for (i, val) in kernel.iter().enumerate() {
+        if val.abs() > kernel[pivot].abs() {
+            pivot = i;
+        }
+    }
+
+    let max = kernel[pivot];
+    if max.abs() <= f64::EPSILON {
+        return Ok(None);
+    }
+
+    let (p_x, p_y) = (pivot % size, pivot / size);
+    let scalar = max.abs().sqrt();
+    let vertical_kernel: Vec<f64> = (0..size)
+        .map(|y| kernel[y * size + p_x] / scalar)
+        .collect();
+    let horizontal_kernel: Vec<f64> = (0..size)
+        .map(|x| kernel[p_y * size + x] / scalar * max.signum())
+        .collect();
 
     // Check if kernel is separable
-    if s.data()[0] != 0.0 {
-        for i in 1..size {
-            if s.data()[i * size + i] != 0.0 {
-                return None;
+    let tolerance = max.abs() * 1e-10;
+    for y in 0..size {
+        for x in 0..size {
+            if (vertical_kernel[y] * horizontal_kernel[x] - kernel[y * size + x]).abs() > tolerance {
+                return Ok(None);
             }
         }
-    } else {
-        return None;
     }
 
-    let scalar = s.data()[0].sqrt();
-    let vertical_kernel = (u.col(0).as_slice().into_matrix() * scalar).into_vec();
-    let horizontal_kernel = (v.transpose().row(0).as_slice().into_matrix() * scalar).into_vec();
-    Some((vertical_kernel, horizontal_kernel))
+    Ok(Some((vertical_kernel, horizontal_kernel)))
 }
 
 /// Returns the maximum of three f64 values
@@ -124,13 +152,23 @@ pub fn min_4(w: f64, x: f64, y: f64, z: f64) -> f64 {
     }
 }
 
+// Returns the dimensions a 1D neighborhood must have to match a kernel of length `len`: a
+// column if `input` is one pixel wide, otherwise a row
+fn kernel_dimensions_1d(input: &SubImage<f64>, len: usize) -> (u32, u32) {
+    if input.info().width == 1 {
+        (1, len as u32)
+    } else {
+        (len as u32, 1)
+    }
+}
+
 /// Applies a 1D kernel
 #[cfg(not(feature = "rayon"))]
 pub fn apply_1d_kernel(input: &SubImage<f64>, output: &mut Vec<f64>, kernel: &[f64]) -> ImgProcResult<()> {
     let size = input.info().size() as usize;
 
     error::check_odd(kernel.len(), "kernel length")?;
-    error::check_equal(kernel.len(), size, "pixels and kernel dimensions")?;
+    error::check_dimensions_equal(kernel_dimensions_1d(input, kernel.len()), input.info().wh())?;
 
     output.clear();
     for _ in 0..input.info().channels {
@@ -153,7 +191,7 @@ pub fn apply_1d_kernel(input: &SubImage<f64>, kernel: &[f64]) -> ImgProcResult<V
     let size = input.info().size() as usize;
 
     error::check_odd(kernel.len(), "kernel length")?;
-    error::check_equal(kernel.len(), size, "pixels and kernel dimensions")?;
+    error::check_dimensions_equal(kernel_dimensions_1d(input, kernel.len()), input.info().wh())?;
 
     let mut output = vec![0.0; input.info().channels as usize];
 
@@ -173,7 +211,9 @@ pub fn apply_2d_kernel(input: &SubImage<f64>, output: &mut Vec<f64>, kernel: &[f
     let size = input.info().width as usize;
 
     error::check_odd(kernel.len(), "kernel length")?;
-    error::check_equal(kernel.len(), size * size, "pixels and kernel dimensions")?;
+    error::check_square(kernel.len() as f64, "kernel length")?;
+    let kernel_size = (kernel.len() as f64).sqrt() as u32;
+    error::check_dimensions_equal((kernel_size, kernel_size), input.info().wh())?;
 
     output.clear();
     for _ in 0..input.info().channels {
@@ -199,7 +239,9 @@ pub fn apply_2d_kernel(input: &SubImage<f64>, kernel: &[f64]) -> ImgProcResult<V
     let size = input.info().width as usize;
 
     error::check_odd(kernel.len(), "kernel length")?;
-    error::check_equal(kernel.len(), size * size, "pixels and kernel dimensions")?;
+    error::check_square(kernel.len() as f64, "kernel length")?;
+    let kernel_size = (kernel.len() as f64).sqrt() as u32;
+    error::check_dimensions_equal((kernel_size, kernel_size), input.info().wh())?;
 
     let mut output = vec![0.0; input.info().channels as usize];
 
@@ -241,6 +283,24 @@ pub fn cubic_weighting_fn(x: f64) -> f64 {
         - 4.0 * clamp_zero(x - 1.0).powf(3.0))
 }
 
+/// Triangle (tent) weighting function, which falls linearly from 1 at `x = 0` to 0 at `|x| = 1`
+pub fn triangle_fn(x: f64) -> f64 {
+    (1.0 - x.abs()).max(0.0)
+}
+
+/// Catmull-Rom cubic weighting function (the Keys cubic kernel with `a = -0.5`)
+pub fn catmull_rom_fn(x: f64) -> f64 {
+    let x = x.abs();
+
+    if x < 1.0 {
+        1.5 * x * x * x - 2.5 * x * x + 1.0
+    } else if x < 2.0 {
+        -0.5 * x * x * x + 2.5 * x * x - 4.0 * x + 2.0
+    } else {
+        0.0
+    }
+}
+
 /// A helper function for the colorspace conversion from CIE XYZ to CIELAB
 pub fn xyz_to_lab_fn(num: f64) -> f64 {
     let d: f64 = 6.0 / 29.0;
@@ -263,6 +323,26 @@ pub fn lab_to_xyz_fn(num: f64) -> f64 {
     }
 }
 
+/// The sRGB electro-optical transfer function: converts an sRGB value in range [0, 1] to linear
+/// light
+pub fn srgb_decode_fn(num: f64) -> f64 {
+    if num <= 0.04045 {
+        num / 12.92
+    } else {
+        ((num + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// The inverse of the sRGB electro-optical transfer function: converts a linear light value in
+/// range [0, 1] to sRGB
+pub fn srgb_encode_fn(num: f64) -> f64 {
+    if num <= 0.0031308 {
+        num * 12.92
+    } else {
+        1.055 * num.powf(1.0 / 2.4) - 0.055
+    }
+}
+
 /// Returns 0 if `x` is less than 0; `x` if not
 pub fn clamp_zero(x: f64) -> f64 {
     if x <= 0.0 {
@@ -290,4 +370,160 @@ pub fn lanczos_kernel(x: f64, a: f64) -> f64 {
     }
 
     0.0
-}
\ No newline at end of file
+}
+
+/// Computes the CIEDE2000 color difference between two CIELAB colors
+pub fn ciede2000_fn(lab_1: &[f64], lab_2: &[f64]) -> f64 {
+    let (l_1, a_1, b_1) = (lab_1[0], lab_1[1], lab_1[2]);
+    let (l_2, a_2, b_2) = (lab_2[0], lab_2[1], lab_2[2]);
+    let pow_25_7 = 25.0f64.powi(7);
+
+    // Adjust a* to account for the neutral colors
+    let c_bar = ((a_1 * a_1 + b_1 * b_1).sqrt() + (a_2 * a_2 + b_2 * b_2).sqrt()) / 2.0;
+    let g = 0.5 * (1.0 - (c_bar.powi(7) / (c_bar.powi(7) + pow_25_7)).sqrt());
+    let a_1 = (1.0 + g) * a_1;
+    let a_2 = (1.0 + g) * a_2;
+
+    let c_1 = (a_1 * a_1 + b_1 * b_1).sqrt();
+    let c_2 = (a_2 * a_2 + b_2 * b_2).sqrt();
+    let h_1 = if c_1 == 0.0 { 0.0 } else { b_1.atan2(a_1).to_degrees().rem_euclid(360.0) };
+    let h_2 = if c_2 == 0.0 { 0.0 } else { b_2.atan2(a_2).to_degrees().rem_euclid(360.0) };
+
+    // Differences in lightness, chroma, and hue
+    let delta_l = l_2 - l_1;
+    let delta_c = c_2 - c_1;
+    let delta_h = if c_1 * c_2 == 0.0 {
+        0.0
+    } else if (h_2 - h_1).abs() <= 180.0 {
+        h_2 - h_1
+    } else if h_2 - h_1 > 180.0 {
+        h_2 - h_1 - 360.0
+    } else {
+        h_2 - h_1 + 360.0
+    };
+    let delta_h = 2.0 * (c_1 * c_2).sqrt() * (delta_h / 2.0).to_radians().sin();
+
+    // Means of lightness, chroma, and hue
+    let l_mean = (l_1 + l_2) / 2.0;
+    let c_mean = (c_1 + c_2) / 2.0;
+    let h_mean = if c_1 * c_2 == 0.0 {
+        h_1 + h_2
+    } else if (h_1 - h_2).abs() <= 180.0 {
+        (h_1 + h_2) / 2.0
+    } else if h_1 + h_2 < 360.0 {
+        (h_1 + h_2 + 360.0) / 2.0
+    } else {
+        (h_1 + h_2 - 360.0) / 2.0
+    };
+
+    // Weighting functions
+    let t = 1.0 - 0.17 * (h_mean - 30.0).to_radians().cos()
+        + 0.24 * (2.0 * h_mean).to_radians().cos()
+        + 0.32 * (3.0 * h_mean + 6.0).to_radians().cos()
+        - 0.20 * (4.0 * h_mean - 63.0).to_radians().cos();
+    let delta_theta = 30.0 * (-((h_mean - 275.0) / 25.0).powi(2)).exp();
+    let r_c = 2.0 * (c_mean.powi(7) / (c_mean.powi(7) + pow_25_7)).sqrt();
+    let s_l = 1.0 + (0.015 * (l_mean - 50.0).powi(2)) / (20.0 + (l_mean - 50.0).powi(2)).sqrt();
+    let s_c = 1.0 + 0.045 * c_mean;
+    let s_h = 1.0 + 0.015 * c_mean * t;
+    let r_t = -(2.0 * delta_theta).to_radians().sin() * r_c;
+
+    let l_term = delta_l / s_l;
+    let c_term = delta_c / s_c;
+    let h_term = delta_h / s_h;
+
+    (l_term * l_term + c_term * c_term + h_term * h_term + r_t * c_term * h_term).sqrt()
+}
+
+/// A square 2D kernel with odd dimensions
+#[derive(Debug, Clone, PartialEq)]
+pub struct Kernel {
+    size: u32,
+    data: Vec<f64>,
+}
+
+impl Kernel {
+    /// Creates a new `size x size` `Kernel` from `data`, given in row-major order
+    pub fn new(size: u32, data: Vec<f64>) -> ImgProcResult<Self> {
+        error::check_odd(size, "size")?;
+        error::check_equal((size * size) as usize, data.len(), "kernel size and data length")?;
+
+        Ok(Kernel { size, data })
+    }
+
+    /// Creates a normalized `size x size` Gaussian kernel with standard deviation `sigma`
+    pub fn gaussian(size: u32, sigma: f64) -> ImgProcResult<Self> {
+        let mut data = super::generate_gaussian_kernel(size, sigma)?;
+        let sum: f64 = data.iter().sum();
+        data.iter_mut().for_each(|val| *val /= sum);
+
+        Kernel::new(size, data)
+    }
+
+    /// Creates a normalized `size x size` box blur kernel
+    pub fn box_blur(size: u32) -> ImgProcResult<Self> {
+        Kernel::new(size, vec![1.0 / ((size * size) as f64); (size * size) as usize])
+    }
+
+    /// Creates a zero-mean Gabor kernel with a sinusoid of wavelength `wavelength` (in pixels)
+    /// oscillating along the direction `orientation` (in degrees), and a Gaussian envelope with
+    /// standard deviation `sigma` and spatial aspect ratio `aspect`. The kernel extends three
+    /// standard deviations from its center
+    pub fn gabor(wavelength: f64, orientation: f64, sigma: f64, aspect: f64) -> ImgProcResult<Self> {
+        error::check_positive(wavelength, "wavelength")?;
+        error::check_positive(sigma, "sigma")?;
+        error::check_positive(aspect, "aspect")?;
+
+        let half = (3.0 * sigma.max(sigma / aspect)).ceil() as i32;
+        let size = (2 * half + 1) as u32;
+        let (sin, cos) = orientation.to_radians().sin_cos();
+
+        let mut data = Vec::with_capacity((size * size) as usize);
+        for y in -half..=half {
+            for x in -half..=half {
+                let x_rot = x as f64 * cos + y as f64 * sin;
+                let y_rot = -(x as f64) * sin + y as f64 * cos;
+                let envelope = (-(x_rot * x_rot + aspect * aspect * y_rot * y_rot) / (2.0 * sigma * sigma)).exp();
+
+                data.push(envelope * (2.0 * PI * x_rot / wavelength).cos());
+            }
+        }
+
+        let mean = data.iter().sum::<f64>() / data.len() as f64;
+        data.iter_mut().for_each(|val| *val -= mean);
+
+        Kernel::new(size, data)
+    }
+
+    /// Creates the 3x3 horizontal Sobel kernel
+    pub fn sobel_x() -> Self {
+        let mut data = Vec::with_capacity(9);
+        for vert in K_SOBEL_1D_VERT.iter() {
+            for horz in K_SOBEL_1D_HORZ.iter() {
+                data.push(vert * horz);
+            }
+        }
+
+        Kernel { size: 3, data }
+    }
+
+    /// Creates the 3x3 Laplacian kernel
+    pub fn laplacian() -> Self {
+        Kernel { size: 3, data: K_LAPLACIAN.to_vec() }
+    }
+
+    /// Creates the 3x3 sharpening kernel
+    pub fn sharpen() -> Self {
+        Kernel { size: 3, data: K_SHARPEN.to_vec() }
+    }
+
+    /// Returns the width (and height) of the kernel
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// Returns the kernel values in row-major order
+    pub fn data(&self) -> &[f64] {
+        &self.data
+    }
+}
@@ -62,9 +62,20 @@ pub fn generate_lookup_table<T: Number, F>(table: &mut [T; 256], f: F)
     }
 }
 
+/// Rounds `channel` to the nearest integer and converts it to a `u8`. Values below 0 and negative
+/// infinity become 0, values above 255 and positive infinity become 255, and NaN becomes 0
+pub(crate) fn round_to_u8(channel: f64) -> u8 {
+    if channel.is_nan() {
+        return 0;
+    }
+
+    channel.round().clamp(0.0, 255.0) as u8
+}
+
 /// Generates a Gaussian kernel
 pub fn generate_gaussian_kernel(size: u32, sigma: f64) -> ImgProcResult<Vec<f64>> {
     error::check_odd(size, "size")?;
+    error::check_positive(sigma, "sigma")?;
 
     let mut filter = vec![0.0; (size * size) as usize];
     let k = ((size - 1) / 2) as i32;
@@ -225,6 +236,57 @@ pub fn rectangular_intensity_sum(summed_area_table: &Image<f64>, x_0: u32, y_0:
     sum
 }
 
+/// Returns a copy of `a` where every pixel that differs from the corresponding pixel in `b` by
+/// more than `threshold` in any channel is painted with `highlight`. Alpha channels are preserved.
+/// `a` and `b` must be RGB or RGBA images with the same dimensions
+pub fn diff_highlight(a: &Image<u8>, b: &Image<u8>, threshold: u8, highlight: [u8; 3]) -> ImgProcResult<Image<u8>> {
+    error::check_rgb(a)?;
+
+    a.zip_map(b, |p_a, p_b, p_out| {
+        let changed = p_a.iter()
+            .zip(p_b.iter())
+            .any(|(c_a, c_b)| (*c_a as i16 - *c_b as i16).abs() > threshold as i16);
+
+        if changed {
+            p_out.extend_from_slice(&highlight);
+            p_out.extend_from_slice(&p_a[3..]);
+        } else {
+            p_out.extend_from_slice(p_a);
+        }
+    })
+}
+
+/// If `input` has an alpha channel, multiplies each color channel by alpha, applies `f`, and then
+/// divides each color channel of the result by its alpha (color channels of fully transparent
+/// pixels are set to 0); otherwise, applies `f` to `input`. This keeps the colors of transparent
+/// pixels from bleeding into their neighbors when filtering or resampling
+pub fn apply_premultiplied<F>(input: &Image<f64>, f: F) -> ImgProcResult<Image<f64>>
+    where F: Fn(&Image<f64>) -> ImgProcResult<Image<f64>> {
+    if !input.info().alpha {
+        return f(input);
+    }
+
+    let premultiplied = input.map_pixels(|channels, p_out| {
+        let alpha = channels[channels.len() - 1];
+        for channel in channels[..(channels.len() - 1)].iter() {
+            p_out.push(channel * alpha);
+        }
+        p_out.push(alpha);
+    });
+
+    Ok(f(&premultiplied)?.map_pixels(|channels, p_out| {
+        let alpha = channels[channels.len() - 1];
+        for channel in channels[..(channels.len() - 1)].iter() {
+            if alpha > f64::EPSILON {
+                p_out.push(channel / alpha);
+            } else {
+                p_out.push(0.0);
+            }
+        }
+        p_out.push(alpha);
+    }))
+}
+
 /// Converts 1D vector index to 2D matrix coordinates
 pub fn get_2d_coords(i: u32, width: u32) -> (u32, u32) {
     let x = i % width;
@@ -0,0 +1,149 @@
+#![allow(dead_code)]
+
+mod common;
+
+use common::noise_image;
+use imgproc_rs::{analysis, filter, io, tone, transform};
+use imgproc_rs::enums::{Refl, Tone};
+use imgproc_rs::image::{Image, ImageInfo, BaseImage};
+
+#[test]
+fn local_binary_pattern_test() {
+    let flat = Image::from_vec(4, 3, 3, false, vec![80; 36]);
+    let output = analysis::local_binary_pattern(&flat).unwrap();
+    assert_eq!(ImageInfo::new(4, 3, 1, false), output.info());
+    assert!(output.data().iter().all(|code| *code == 255));
+
+    // Neighbors of the center pixel, clockwise from the upper left: 10, 20, 30, 30, 30, 20, 10, 10
+    let gradient = Image::from_vec(3, 3, 1, false, vec![10, 20, 30, 10, 20, 30, 10, 20, 30]);
+    let output = analysis::local_binary_pattern(&gradient).unwrap();
+    assert_eq!(0b0011_1110, output.get_pixel(1, 1)[0]);
+
+    let empty: Image<u8> = Image::blank(ImageInfo::new(0, 3, 3, false));
+    assert!(analysis::local_binary_pattern(&empty).is_err());
+}
+
+#[test]
+fn hog_test() {
+    // A vertical edge has a purely horizontal gradient, which falls in bin 0
+    let mut data = Vec::new();
+    for _ in 0..16 {
+        data.extend_from_slice(&[0u8; 8]);
+        data.extend_from_slice(&[255u8; 8]);
+    }
+    let img = Image::from_vec(16, 16, 1, false, data);
+
+    let bins = 9;
+    let output = analysis::hog(&img, 4, bins).unwrap();
+    assert_eq!(3 * 3 * 4 * bins as usize, output.len());
+
+    let mut energy = vec![0.0; bins as usize];
+    for (i, val) in output.iter().enumerate() {
+        energy[i % bins as usize] += val;
+    }
+    assert!(energy[0] > 0.0);
+    assert!(energy[1..].iter().all(|val| *val < 0.01 * energy[0]));
+
+    assert!(analysis::hog(&img, 0, bins).is_err());
+    assert!(analysis::hog(&img, 16, bins).is_err());
+}
+
+// Blurred noise, which has plenty of low frequency detail and is only changed slightly by JPEG
+// compression
+fn smooth_noise_image(seed: u64) -> Image<u8> {
+    let img: Image<f64> = noise_image(96, 64, 3, seed).into();
+    filter::gaussian_blur(&img, 13, 2.0).unwrap().into()
+}
+
+#[test]
+fn phash_test() {
+    let img = smooth_noise_image(1);
+
+    let path = std::env::temp_dir().join(format!("imgproc_rs_phash_{}.jpg", std::process::id()));
+    io::write(&img, path.to_str().unwrap()).unwrap();
+    let jpeg = io::read(path.to_str().unwrap()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let hash = analysis::phash(&img);
+    assert!(analysis::hamming_distance(hash, analysis::phash(&jpeg)) <= 4);
+    assert!(analysis::hamming_distance(hash, analysis::phash(&smooth_noise_image(2))) >= 20);
+
+    assert_eq!(0, analysis::hamming_distance(0xF0F0, 0xF0F0));
+    assert_eq!(8, analysis::hamming_distance(0xF0F0, 0x0FF0));
+}
+
+#[test]
+fn ahash_dhash_test() {
+    let img = smooth_noise_image(3);
+    let flipped = transform::reflect(&img, Refl::Vertical).unwrap();
+    let brighter = tone::brightness(&img, 30, Tone::Rgb).unwrap();
+
+    let hash = analysis::dhash(&img);
+    assert!(analysis::hamming_distance(hash, analysis::dhash(&flipped)) >= 20);
+    assert!(analysis::hamming_distance(hash, analysis::dhash(&brighter)) <= 4);
+
+    let hash = analysis::ahash(&img);
+    assert!(analysis::hamming_distance(hash, analysis::ahash(&brighter)) <= 4);
+    assert!(analysis::hamming_distance(hash, analysis::ahash(&smooth_noise_image(4))) >= 16);
+
+    let empty = Image::from_vec(0, 0, 3, false, Vec::new());
+    assert_eq!(0, analysis::ahash(&empty));
+    assert_eq!(0, analysis::dhash(&empty));
+}
+
+#[test]
+fn count_colors_test() {
+    let two = Image::from_vec(3, 2, 3, false, vec![255, 0, 0, 0, 0, 255, 255, 0, 0,
+                                                   0, 0, 255, 255, 0, 0, 255, 0, 0]);
+    assert_eq!(2, analysis::count_colors(&two));
+    assert_eq!(vec![vec![0, 0, 255], vec![255, 0, 0]], analysis::unique_colors(&two));
+
+    // Every column of the gradient has a distinct color
+    let gradient = Image::linear_gradient(64, 4, &[0, 0, 0], &[255, 128, 64], true).unwrap();
+    assert_eq!(64, analysis::count_colors(&gradient));
+    assert_eq!(64, analysis::unique_colors(&gradient).len());
+
+    // Alpha is part of the color
+    let rgba = Image::from_vec(2, 1, 4, true, vec![10, 20, 30, 0, 10, 20, 30, 255]);
+    assert_eq!(2, analysis::count_colors(&rgba));
+}
+
+#[test]
+fn first_difference_test() {
+    let a = noise_image(7, 5, 3, 12);
+    assert_eq!(None, analysis::first_difference(&a, &a).unwrap());
+
+    let mut b = a.clone();
+    let mut pixel = b.get_pixel(4, 2).to_vec();
+    pixel[1] = pixel[1].wrapping_add(1);
+    b.set_pixel(4, 2, &pixel);
+    pixel[2] = pixel[2].wrapping_add(1);
+    b.set_pixel(5, 3, &pixel);
+    assert_eq!(Some((4, 2, 1)), analysis::first_difference(&a, &b).unwrap());
+
+    assert!(analysis::first_difference(&a, &noise_image(5, 7, 3, 12)).is_err());
+    assert!(analysis::first_difference(&a, &noise_image(7, 5, 1, 12)).is_err());
+}
+
+#[test]
+fn running_stats_test() {
+    let img = noise_image(37, 23, 4, 17);
+    let (width, channels) = (img.info().width as usize, img.info().channels);
+    let expected = analysis::channel_stats(&img);
+
+    let mut stats = analysis::RunningStats::new();
+    for row in img.data().chunks(width * channels as usize) {
+        stats.update(row, channels);
+    }
+    let output = stats.finish();
+
+    for c in 0..4 {
+        assert!((expected.mean[c] - output.mean[c]).abs() < 1e-9);
+        assert!((expected.variance[c] - output.variance[c]).abs() < 1e-6);
+    }
+
+    let flat = Image::from_vec(2, 2, 1, false, vec![0, 0, 4, 4]);
+    let stats = analysis::channel_stats(&flat);
+    assert_eq!(vec![2.0], stats.mean);
+    assert_eq!(vec![4.0], stats.variance);
+}
@@ -4,7 +4,7 @@ mod common;
 
 use common::setup;
 use imgproc_rs::{colorspace, convert};
-use imgproc_rs::image::Image;
+use imgproc_rs::image::{Image, ImageInfo, BaseImage};
 use imgproc_rs::io::write;
 
 use std::time::SystemTime;
@@ -106,4 +106,163 @@ fn hsv_to_rgb_test() {
     println!("processing: {}", now.elapsed().unwrap().as_millis());
 
     write(&proc, "images/tests/colorspace/hsv_rgb.png").unwrap();
-}
\ No newline at end of file
+}
+
+fn hue(p: &[f64]) -> f64 {
+    let max = p[0].max(p[1]).max(p[2]);
+    let min = p[0].min(p[1]).min(p[2]);
+    let range = max - min;
+
+    if max == p[0] {
+        ((p[1] - p[2]) / range).rem_euclid(6.0)
+    } else if max == p[1] {
+        (p[2] - p[0]) / range + 2.0
+    } else {
+        (p[0] - p[1]) / range + 4.0
+    }
+}
+
+#[test]
+fn clamp_to_gamut_test() {
+    let in_gamut = Image::from_slice(2, 1, 3, false, &[0.2, 0.5, 0.9, 1.0, 0.0, 0.3]);
+    assert_eq!(in_gamut, colorspace::clamp_to_gamut(&in_gamut));
+
+    let original = [1.2, 0.5, -0.1];
+    let out_of_gamut = Image::from_slice(1, 1, 3, false, &original);
+    let clamped = colorspace::clamp_to_gamut(&out_of_gamut);
+    let p = clamped.data();
+
+    for channel in p.iter() {
+        assert!(*channel >= 0.0 && *channel <= 1.0);
+    }
+
+    let naive = [1.0, 0.5, 0.0];
+    let hue_err = (hue(p) - hue(&original)).abs();
+    let naive_hue_err = (hue(&naive) - hue(&original)).abs();
+    assert!(hue_err < naive_hue_err);
+    assert!(hue_err < 1e-9);
+}
+
+#[test]
+fn srgb_linear_round_trip_test() {
+    let data: Vec<u8> = (0..=255).collect();
+    let img = Image::from_vec(256, 1, 1, false, data);
+    let linear = colorspace::srgb_to_linear(&img);
+
+    for channel in linear.data().iter() {
+        assert!(*channel >= 0.0 && *channel <= 1.0);
+    }
+    assert_eq!(img, colorspace::linear_to_srgb(&linear));
+}
+
+#[test]
+fn srgb_linear_average_test() {
+    let img = Image::from_slice(2, 1, 1, false, &[0u8, 255]);
+    let linear = colorspace::srgb_to_linear(&img);
+    let avg = Image::from_slice(1, 1, 1, false, &[(linear.data()[0] + linear.data()[1]) / 2.0]);
+
+    assert_eq!(188, colorspace::linear_to_srgb(&avg).data()[0]);
+}
+
+#[test]
+fn swap_channels_test() {
+    let img = Image::from_slice(2, 1, 3, false, &[255u8, 0, 0, 10, 20, 30]);
+
+    let swapped = colorspace::swap_channels(&img, 0, 2).unwrap();
+    assert_eq!(&[0, 0, 255, 30, 20, 10], swapped.data());
+    assert_eq!(swapped, colorspace::bgr_to_rgb(&img).unwrap());
+
+    assert_eq!(img, colorspace::swap_channels(&swapped, 2, 0).unwrap());
+    assert_eq!(img, colorspace::swap_channels(&img, 1, 1).unwrap());
+
+    assert!(colorspace::swap_channels(&img, 0, 3).is_err());
+    assert!(colorspace::bgr_to_rgb(&Image::from_slice(1, 1, 2, true, &[0u8, 255])).is_err());
+}
+
+#[test]
+fn extract_channel_test() {
+    let img = Image::from_slice(2, 2, 3, false, &[255u8, 0, 0, 10, 20, 30,
+                                                  40, 50, 60, 70, 80, 90]);
+
+    let red = colorspace::extract_channel(&img, 0).unwrap();
+    assert_eq!(ImageInfo::new(2, 2, 1, false), red.info());
+    assert_eq!(&[255, 10, 40, 70], red.data());
+    assert_eq!(&[0, 30, 60, 90], colorspace::extract_channel(&img, 2).unwrap().data());
+
+    assert!(colorspace::extract_channel(&img, 3).is_err());
+}
+
+#[test]
+fn map_selected_channels_test() {
+    let img = Image::from_slice(2, 1, 3, false, &[255u8, 0, 0, 10, 20, 30]);
+
+    let inverted = colorspace::map_selected_channels(&img, &[0], |val| 255 - val).unwrap();
+    assert_eq!(&[0, 0, 0, 245, 20, 30], inverted.data());
+
+    let both = colorspace::map_selected_channels(&img, &[2, 1, 2], |val| val / 2).unwrap();
+    assert_eq!(&[255, 0, 0, 10, 10, 15], both.data());
+    assert_eq!(img, colorspace::map_selected_channels(&img, &[], |val| 255 - val).unwrap());
+
+    assert!(colorspace::map_selected_channels(&img, &[3], |val| val).is_err());
+}
+
+#[test]
+fn premultiply_alpha_test() {
+    let img = Image::from_slice(4, 1, 4, true, &[200u8, 100, 50, 255, 200, 100, 50, 200,
+                                                  255, 17, 0, 128, 200, 100, 50, 0]);
+
+    let premultiplied = colorspace::premultiply_alpha(&img).unwrap();
+    assert_eq!(&[200, 100, 50, 255], premultiplied.get_pixel(0, 0));
+    assert_eq!(&[157, 78, 39, 200], premultiplied.get_pixel(1, 0));
+    assert_eq!(&[0, 0, 0, 0], premultiplied.get_pixel(3, 0));
+
+    let output = colorspace::unpremultiply_alpha(&premultiplied).unwrap();
+    for x in 0..3 {
+        for (a, b) in img.get_pixel(x, 0).iter().zip(output.get_pixel(x, 0).iter()) {
+            assert!((*a as i32 - *b as i32).abs() <= 1);
+        }
+    }
+    assert_eq!(&[0, 0, 0, 0], output.get_pixel(3, 0));
+
+    let rgb = Image::from_slice(1, 1, 3, false, &[1u8, 2, 3]);
+    assert!(colorspace::premultiply_alpha(&rgb).is_err());
+    assert!(colorspace::unpremultiply_alpha(&rgb).is_err());
+}
+
+#[test]
+fn delta_e_cie76_test() {
+    let a = Image::from_slice(2, 1, 3, false, &[0u8, 0, 0, 255, 0, 0]);
+    let b = Image::from_slice(2, 1, 3, false, &[255u8, 255, 255, 0, 0, 255]);
+
+    let same = colorspace::delta_e_cie76(&a, &a).unwrap();
+    assert_eq!(ImageInfo::new(2, 1, 1, false), same.info());
+    assert!(same.data().iter().all(|val| *val == 0.0));
+
+    let diff = colorspace::delta_e_cie76(&a, &b).unwrap();
+    assert!((diff.data()[0] - 100.0).abs() < 0.1);
+    assert!((diff.data()[1] - 176.3).abs() < 0.5);
+
+    let c = Image::from_slice(1, 1, 3, false, &[0u8, 0, 0]);
+    assert!(colorspace::delta_e_cie76(&a, &c).is_err());
+}
+
+#[test]
+fn delta_e_ciede2000_test() {
+    let a = Image::from_slice(2, 1, 3, false, &[0u8, 0, 0, 120, 40, 200]);
+    let b = Image::from_slice(2, 1, 3, false, &[255u8, 255, 255, 120, 40, 200]);
+
+    assert!(colorspace::delta_e_ciede2000(&a, &a).unwrap().data().iter().all(|val| *val == 0.0));
+
+    let diff = colorspace::delta_e_ciede2000(&a, &b).unwrap();
+    assert!((diff.data()[0] - 100.0).abs() < 0.1);
+    assert_eq!(0.0, diff.data()[1]);
+}
+
+#[test]
+fn grayscale_conversion_test() {
+    let gray = Image::from_vec(3, 1, 1, false, vec![0, 90, 255]);
+    let rgb = Image::from_vec(3, 1, 3, false, vec![0, 0, 0, 90, 90, 90, 255, 255, 255]);
+
+    assert_eq!(colorspace::srgb_to_lab(&rgb, &White::D65), colorspace::srgb_to_lab(&gray, &White::D65));
+    assert_eq!(colorspace::rgb_to_hsv(&rgb), colorspace::rgb_to_hsv(&gray));
+}
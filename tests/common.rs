@@ -9,4 +9,18 @@ pub fn setup(path: &str) -> ImgIoResult<Image<u8>> {
     println!("{}", img.info());
 
     Ok(img)
-}
\ No newline at end of file
+}
+
+/// Generates a deterministic pseudo-random image for comparing filters against reference
+/// implementations
+pub fn noise_image(width: u32, height: u32, channels: u8, seed: u64) -> Image<u8> {
+    let mut state = seed;
+    let data = (0..(width * height * channels as u32))
+        .map(|_| {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (state >> 33) as u8
+        })
+        .collect();
+
+    Image::from_vec(width, height, channels, false, data)
+}
@@ -0,0 +1,46 @@
+use imgproc_rs::convert;
+use imgproc_rs::image::Image;
+
+#[test]
+fn clamp_channels_test() {
+    let img = Image::from_slice(2, 1, 2, true, &[-10.0, 0.5, 300.0, 1.5]);
+    assert_eq!(&[0.0, 0.5, 1.0, 1.0], convert::clamp_channels(&img, 0.0, 1.0).data());
+}
+
+#[test]
+fn f64_to_u8_scale_test() {
+    let img = Image::from_slice(4, 1, 1, false, &[-10.0, 0.0, 127.6, 300.0]);
+    assert_eq!(&[0, 0, 128, 255], convert::f64_to_u8_scale(&img, 255).data());
+
+    let img = Image::from_slice(3, 1, 1, false, &[-0.5, 0.5, 2.0]);
+    assert_eq!(&[0, 128, 255], convert::f64_to_u8_scale(&img, 1).data());
+}
+
+#[test]
+fn non_finite_to_u8_test() {
+    let img = Image::from_slice(4, 1, 1, false, &[f64::NAN, f64::INFINITY, f64::NEG_INFINITY, 100.4]);
+    assert_eq!(&[0, 255, 0, 100], convert::f64_to_u8_scale(&img, 255).data());
+
+    let output: Image<u8> = img.into();
+    assert_eq!(&[0, 255, 0, 100], output.data());
+
+    let img = Image::from_slice(3, 1, 1, false, &[f32::NAN, f32::INFINITY, 300.0]);
+    let output: Image<u8> = img.into();
+    assert_eq!(&[0, 255, 255], output.data());
+}
+
+#[test]
+fn u16_f64_round_trip_test() {
+    // A 16-bit depth gradient covering the full range
+    let data: Vec<u16> = (0..256u32).map(|i| (i * 65535 / 255) as u16).chain(vec![1, 2, 65534]).collect();
+    let depth = Image::from_vec(259, 1, 1, false, data);
+
+    for scale in [1, 255, 65535].iter() {
+        let f = convert::u16_to_f64_scale(&depth, *scale);
+        assert_eq!(*scale as f64, f.data()[255]);
+        assert_eq!(depth, convert::f64_to_u16_scale(&f, *scale));
+    }
+
+    let img = Image::from_slice(3, 1, 1, false, &[-1.0, f64::NAN, 2.0]);
+    assert_eq!(&[0, 0, 65535], convert::f64_to_u16_scale(&img, 1).data());
+}
@@ -2,13 +2,17 @@
 
 mod common;
 
-use common::setup;
-use imgproc_rs::{filter, colorspace};
-use imgproc_rs::image::Image;
+use common::{setup, noise_image};
+use imgproc_rs::{analysis, filter, colorspace};
+use imgproc_rs::image::{Image, ImageInfo, BaseImage, Rect};
 use imgproc_rs::io::write;
+use imgproc_rs::util::Kernel;
+use imgproc_rs::error::ImgProcError;
 
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::SystemTime;
-use imgproc_rs::enums::{Bilateral, Thresh};
+use imgproc_rs::enums::{Bilateral, ConvMode, FrequencyFilter, Padding, Rolloff, Thresh};
 
 const PATH: &str = "images/yosemite.jpg";
 
@@ -78,6 +82,25 @@ fn bilateral_filter() {
     write(&direct.into(), "images/tests/filter/bilateral_direct.png").unwrap();
 }
 
+#[test]
+fn joint_bilateral_test() {
+    let img = noise_image(10, 8, 3, 4);
+    let plain = filter::bilateral_filter(&img, 10.0, 2.0, Bilateral::Direct).unwrap();
+    assert_eq!(plain, filter::joint_bilateral(&img, &img, 4, 2.0, 10.0).unwrap());
+
+    // A flat guide reduces the filter to a Gaussian blur, which differs from the plain filter
+    let flat = Image::from_vec(10, 8, 3, false, vec![128; 240]);
+    assert_ne!(plain, filter::joint_bilateral(&img, &flat, 4, 2.0, 10.0).unwrap());
+
+    let small = noise_image(9, 8, 3, 4);
+    assert!(matches!(filter::joint_bilateral(&img, &small, 4, 2.0, 10.0),
+                     Err(ImgProcError::DimensionMismatch { expected: (10, 8), found: (9, 8) })));
+    assert!(filter::joint_bilateral(&img, &noise_image(10, 8, 4, 4), 4, 2.0, 10.0).is_err());
+
+    let empty: Image<u8> = Image::blank(ImageInfo::new(0, 8, 3, false));
+    assert!(filter::joint_bilateral(&empty, &empty, 4, 2.0, 10.0).is_err());
+}
+
 // #[test]
 fn sharpen() {
     let img: Image<f64> = setup(PATH).unwrap().into();
@@ -184,4 +207,644 @@ fn threshold_test() {
     write(&trunc.into(), "images/tests/filter/thresh_trunc.png").unwrap();
     write(&zero.into(), "images/tests/filter/thresh_to_zero.png").unwrap();
     write(&zero_inv.into(), "images/tests/filter/thresh_to_zero_inv.png").unwrap();
-}
\ No newline at end of file
+}
+
+// Applies a mean filter over a `(2 * radius + 1) x (2 * radius + 1)` kernel, repeating edge pixels
+fn naive_box_filter(input: &Image<u8>, radius: u32) -> Image<u8> {
+    let (width, height, channels) = input.info().whc();
+    let r = radius as i32;
+    let mut output = Image::blank(input.info());
+
+    for y in 0..height {
+        for x in 0..width {
+            let mut p_out = Vec::new();
+            for c in 0..(channels as usize) {
+                let mut sum = 0.0;
+                for j in -r..=r {
+                    for i in -r..=r {
+                        let x_clamp = (x as i32 + i).clamp(0, width as i32 - 1) as u32;
+                        let y_clamp = (y as i32 + j).clamp(0, height as i32 - 1) as u32;
+                        sum += input.get_pixel(x_clamp, y_clamp)[c] as f32;
+                    }
+                }
+                p_out.push((sum / ((2 * r + 1) * (2 * r + 1)) as f32).round() as u8);
+            }
+            output.set_pixel(x, y, &p_out);
+        }
+    }
+
+    output
+}
+
+#[test]
+fn alpha_trimmed_mean_zero_alpha_test() {
+    for (radius, seed) in [(1, 1), (2, 2), (3, 3)].iter() {
+        let img = noise_image(13, 9, 3, *seed);
+        let filtered = filter::alpha_trimmed_mean_filter(&img, *radius, 0).unwrap();
+
+        assert_eq!(naive_box_filter(&img, *radius), filtered);
+    }
+}
+
+// Applies an alpha-trimmed mean filter by sorting each kernel, repeating edge pixels
+fn naive_alpha_trimmed_mean(input: &Image<u8>, radius: u32, alpha: u32) -> Image<u8> {
+    let (width, height, channels) = input.info().whc();
+    let r = radius as i32;
+    let trim = (alpha / 2) as usize;
+    let mut output = Image::blank(input.info());
+
+    for y in 0..height {
+        for x in 0..width {
+            let mut p_out = Vec::new();
+            for c in 0..(channels as usize) {
+                let mut vals = Vec::new();
+                for j in -r..=r {
+                    for i in -r..=r {
+                        let x_clamp = (x as i32 + i).clamp(0, width as i32 - 1) as u32;
+                        let y_clamp = (y as i32 + j).clamp(0, height as i32 - 1) as u32;
+                        vals.push(input.get_pixel(x_clamp, y_clamp)[c] as u32);
+                    }
+                }
+                vals.sort_unstable();
+
+                let kept = &vals[trim..(vals.len() - trim)];
+                let mean = kept.iter().sum::<u32>() as f64 / kept.len() as f64;
+                p_out.push(mean.round() as u8);
+            }
+            output.set_pixel(x, y, &p_out);
+        }
+    }
+
+    output
+}
+
+#[test]
+fn alpha_trimmed_mean_rounding_test() {
+    // After trimming 100 and 0, seven values remain. Their sums are 7 * 10 + 3 and 7 * 10 + 4,
+    // which are the closest a mean can get to 10.5 from either side
+    let below = Image::from_vec(3, 3, 1, false, vec![0, 10, 10, 10, 10, 10, 10, 13, 100]);
+    let above = Image::from_vec(3, 3, 1, false, vec![0, 10, 10, 10, 10, 10, 10, 14, 100]);
+    assert_eq!(10, filter::alpha_trimmed_mean_filter(&below, 1, 2).unwrap().get_pixel(1, 1)[0]);
+    assert_eq!(11, filter::alpha_trimmed_mean_filter(&above, 1, 2).unwrap().get_pixel(1, 1)[0]);
+
+    for (radius, alpha, seed) in [(1, 2, 6), (2, 4, 7), (2, 10, 8), (3, 16, 9)].iter() {
+        let img = noise_image(13, 9, 3, *seed);
+        let filtered = filter::alpha_trimmed_mean_filter(&img, *radius, *alpha).unwrap();
+
+        assert_eq!(naive_alpha_trimmed_mean(&img, *radius, *alpha), filtered);
+    }
+}
+
+#[test]
+fn median_filter_right_edge_test() {
+    // With a radius of 2, columns are processed in bands of 7, which does not divide 12
+    let img = noise_image(12, 9, 3, 4);
+    let filtered = filter::median_filter(&img, 2).unwrap();
+    let expected = filter::median_filter_naive(&img, 2).unwrap();
+
+    for y in 0..9 {
+        for x in 7..12 {
+            assert_eq!(expected.get_pixel(x, y), filtered.get_pixel(x, y));
+        }
+    }
+    assert_eq!(expected, filtered);
+}
+
+#[test]
+fn median_filter_large_radius_test() {
+    let img = noise_image(3, 3, 1, 5);
+
+    let filtered = filter::median_filter(&img, 5).unwrap();
+    assert_eq!(filter::median_filter_naive(&img, 5).unwrap(), filtered);
+
+    let filtered = filter::alpha_trimmed_mean_filter(&img, 5, 0).unwrap();
+    assert_eq!(naive_box_filter(&img, 5), filtered);
+
+    let flat = Image::from_vec(3, 3, 1, false, vec![42; 9]);
+    assert_eq!(flat, filter::median_filter(&flat, 5).unwrap());
+    assert_eq!(flat, filter::alpha_trimmed_mean_filter(&flat, 5, 10).unwrap());
+
+    let empty: Image<u8> = Image::blank(ImageInfo::new(4, 0, 1, false));
+    assert!(filter::median_filter(&empty, 1).is_err());
+    assert!(filter::alpha_trimmed_mean_filter(&empty, 1, 0).is_err());
+}
+
+#[test]
+fn median_filter_naive_test() {
+    let img = noise_image(17, 11, 3, 6);
+
+    for radius in 0..5 {
+        let expected = filter::median_filter_naive(&img, radius).unwrap();
+        assert_eq!(expected, filter::median_filter(&img, radius).unwrap());
+    }
+}
+
+#[test]
+fn median_filter_f64_test() {
+    let img = noise_image(11, 8, 3, 7);
+
+    for radius in 1..4 {
+        let img_f64: Image<f64> = img.clone().into();
+        let filtered: Image<u8> = filter::median_filter_f64(&img_f64, radius).unwrap().into();
+        assert_eq!(filter::median_filter(&img, radius).unwrap(), filtered);
+    }
+
+    let empty: Image<f64> = Image::blank(ImageInfo::new(0, 3, 1, false));
+    assert!(filter::median_filter_f64(&empty, 1).is_err());
+}
+
+#[test]
+fn gaussian_kernel_test() {
+    let kernel = filter::GaussianKernel::new(5, 1.5).unwrap();
+
+    for seed in 8..11 {
+        let img: Image<f64> = noise_image(9, 7, 3, seed).into();
+        assert_eq!(filter::gaussian_blur(&img, 5, 1.5).unwrap(), kernel.apply(&img).unwrap());
+    }
+    assert!(filter::GaussianKernel::new(4, 1.5).is_err());
+    assert!(filter::GaussianKernel::new(5, f64::NAN).is_err());
+}
+
+#[test]
+fn gaussian_blur_padded_test() {
+    // A constant image stays uniform, with no bias at the edges
+    let flat = Image::from_vec(9, 7, 3, false, vec![200.0; 9 * 7 * 3]);
+    let output = filter::gaussian_blur_padded(&flat, 15, 4.0, Padding::Reflect).unwrap();
+    let center = output.get_pixel(4, 3)[0];
+    assert!(output.data().iter().all(|val| (val - center).abs() < 1e-9));
+
+    // Clamp padding matches the default Gaussian blur
+    let noise: Image<f64> = noise_image(11, 8, 3, 17).into();
+    assert_eq!(filter::gaussian_blur(&noise, 5, 1.5).unwrap(),
+               filter::gaussian_blur_padded(&noise, 5, 1.5, Padding::Clamp).unwrap());
+
+    // An edge close to the left border matches blurring an explicitly mirrored image
+    let (width, height, pad) = (12, 10, 7);
+    let step = |x: i32| if x < 3 { 0.0 } else { 255.0 };
+    let reflect = |x: i32| if x < 0 { -x } else if x >= width { 2 * (width - 1) - x } else { x };
+    let input = Image::from_vec(width as u32, height as u32, 1, false,
+                                (0..height).flat_map(|_| (0..width).map(step)).collect());
+    let mirrored = Image::from_vec((width + 2 * pad) as u32, (height + 2 * pad) as u32, 1, false,
+                                   (0..(height + 2 * pad))
+                                       .flat_map(|_| (-pad..(width + pad)).map(|x| step(reflect(x))))
+                                       .collect());
+
+    let output = filter::gaussian_blur_padded(&input, 15, 4.0, Padding::Reflect).unwrap();
+    let expected = filter::gaussian_blur_padded(&mirrored, 15, 4.0, Padding::Reflect).unwrap();
+    for y in 0..(height as u32) {
+        for x in 0..(width as u32) {
+            let p_exp = expected.get_pixel(x + pad as u32, y + pad as u32);
+            assert!((output.get_pixel(x, y)[0] - p_exp[0]).abs() < 1e-9);
+        }
+    }
+
+    let clamped = filter::gaussian_blur_padded(&input, 15, 4.0, Padding::Clamp).unwrap();
+    assert!((clamped.get_pixel(0, 0)[0] - output.get_pixel(0, 0)[0]).abs() > 1.0);
+
+    assert!(filter::gaussian_blur_padded(&flat, 4, 4.0, Padding::Reflect).is_err());
+    assert!(filter::gaussian_blur_padded(&flat, 5, 0.0, Padding::Reflect).is_err());
+}
+
+#[test]
+fn gaussian_pyramid_test() {
+    let img: Image<f64> = noise_image(13, 10, 3, 9).into();
+    let pyramid = filter::gaussian_pyramid(&img, 4).unwrap();
+
+    assert_eq!(4, pyramid.len());
+    assert_eq!(img, pyramid[0]);
+    assert_eq!((7, 5), pyramid[1].info().wh());
+    assert_eq!((4, 3), pyramid[2].info().wh());
+    assert_eq!((2, 2), pyramid[3].info().wh());
+
+    assert!(filter::gaussian_pyramid(&img, 0).is_err());
+    assert_eq!(5, filter::gaussian_pyramid(&img, 5).unwrap().len());
+    assert!(filter::gaussian_pyramid(&img, 6).is_err());
+}
+
+#[test]
+fn laplacian_pyramid_test() {
+    let img: Image<f64> = noise_image(13, 10, 3, 10).into();
+    let pyramid = filter::laplacian_pyramid(&img, 4).unwrap();
+    let gaussian = filter::gaussian_pyramid(&img, 4).unwrap();
+
+    assert_eq!(gaussian[3], pyramid[3]);
+
+    let output = filter::collapse_laplacian_pyramid(&pyramid).unwrap();
+    for (a, b) in img.data().iter().zip(output.data().iter()) {
+        assert!((a - b).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn blend_pyramid_test() {
+    let (width, height) = (32, 8);
+    let a = Image::from_vec(width, height, 1, false, vec![200; (width * height) as usize]);
+    let b = Image::from_vec(width, height, 1, false, vec![0; (width * height) as usize]);
+
+    // Fades from a to b between x = 12 and x = 20
+    let mut mask = Image::blank(ImageInfo::new(width, height, 1, false));
+    for y in 0..height {
+        for x in 0..width {
+            let weight = (20.0 - x as f64) / 8.0;
+            mask.set_pixel(x, y, &[(weight.clamp(0.0, 1.0) * 255.0).round() as u8]);
+        }
+    }
+
+    let output = filter::blend_pyramid(&a, &b, &mask, 3).unwrap();
+    for y in 0..height {
+        assert!(output.get_pixel(0, y)[0] >= 199);
+        assert!(output.get_pixel(width - 1, y)[0] <= 1);
+
+        for x in 1..width {
+            let prev = output.get_pixel(x - 1, y)[0] as i32;
+            let curr = output.get_pixel(x, y)[0] as i32;
+            assert!(curr <= prev + 1);
+            assert!(prev - curr <= 40);
+        }
+    }
+
+    let c = Image::from_vec(width, height, 3, false, vec![0; (width * height * 3) as usize]);
+    assert!(filter::blend_pyramid(&a, &c, &mask, 3).is_err());
+}
+
+#[test]
+fn convolve_valid_test() {
+    let img: Image<f64> = noise_image(5, 5, 2, 11).into();
+    let kernel = Kernel::new(3, vec![0.0, -1.0, 0.5, 2.0, 1.0, -0.5, 0.25, 0.0, 1.5]).unwrap();
+
+    let same = filter::convolve(&img, &kernel, ConvMode::Same).unwrap();
+    let valid = filter::convolve(&img, &kernel, ConvMode::Valid).unwrap();
+    assert_eq!(ImageInfo::new(3, 3, 2, false), valid.info());
+
+    for y in 0..3 {
+        for x in 0..3 {
+            for (a, b) in valid.get_pixel(x, y).iter().zip(same.get_pixel(x + 1, y + 1).iter()) {
+                assert!((a - b).abs() < 1e-9);
+            }
+        }
+    }
+
+    assert!(filter::convolve(&img, &Kernel::box_blur(7).unwrap(), ConvMode::Valid).is_err());
+}
+
+#[test]
+fn gaussian_blur_premultiplied_test() {
+    // Left half is opaque red, right half is fully transparent black
+    let mut data = Vec::new();
+    for _ in 0..6 {
+        for x in 0..8 {
+            if x < 4 {
+                data.extend_from_slice(&[255.0, 0.0, 0.0, 255.0]);
+            } else {
+                data.extend_from_slice(&[0.0, 0.0, 0.0, 0.0]);
+            }
+        }
+    }
+
+    let rgba = Image::from_vec(8, 6, 4, true, data);
+    let output = filter::gaussian_blur_premultiplied(&rgba, 5, 1.5).unwrap();
+    for y in 0..6 {
+        for x in 0..8 {
+            let pixel = output.get_pixel(x, y);
+            if pixel[3] > 0.0 {
+                assert!((pixel[0] - 255.0).abs() < 1e-9);
+                assert!(pixel[1].abs() < 1e-9 && pixel[2].abs() < 1e-9);
+            }
+        }
+    }
+    assert!(output.get_pixel(4, 3)[3] > 0.0 && output.get_pixel(4, 3)[3] < 255.0);
+
+    // Without premultiplication, the red edge is darkened by the transparent black pixels
+    let naive = filter::gaussian_blur(&rgba, 5, 1.5).unwrap();
+    assert!(naive.get_pixel(3, 3)[0] < 250.0);
+
+    let boxed = filter::box_filter_premultiplied(&rgba, 3).unwrap();
+    assert!((boxed.get_pixel(4, 3)[0] - 255.0).abs() < 1e-9);
+    assert!(filter::box_filter(&rgba, 3).unwrap().get_pixel(4, 3)[0] < 250.0);
+}
+
+#[test]
+fn median_filter_progress_test() {
+    let img = noise_image(23, 6, 3, 13);
+    let calls = RefCell::new(Vec::new());
+
+    let output = filter::median_filter_monitored(&img, 2, Some(&|p| calls.borrow_mut().push(p)), None).unwrap();
+    assert_eq!(filter::median_filter(&img, 2).unwrap(), output);
+
+    let calls = calls.into_inner();
+    assert_eq!(4, calls.len());
+    assert!(calls.windows(2).all(|w| w[0] < w[1]));
+    assert!(calls[0] > 0.0 && (calls[calls.len() - 1] - 1.0).abs() < 1e-6);
+
+    let calls = RefCell::new(Vec::new());
+    filter::alpha_trimmed_mean_filter_monitored(&img, 1, 2, Some(&|p| calls.borrow_mut().push(p)), None).unwrap();
+    let calls = calls.into_inner();
+    assert!(calls.windows(2).all(|w| w[0] < w[1]));
+    assert!((calls[calls.len() - 1] - 1.0).abs() < 1e-6);
+}
+
+#[test]
+fn median_filter_monitored_test() {
+    let img = noise_image(15, 6, 3, 14);
+
+    let cancel = AtomicBool::new(false);
+    let output = filter::median_filter_monitored(&img, 1, None, Some(&cancel)).unwrap();
+    assert_eq!(filter::median_filter(&img, 1).unwrap(), output);
+    let output = filter::alpha_trimmed_mean_filter_monitored(&img, 1, 2, None, Some(&cancel)).unwrap();
+    assert_eq!(filter::alpha_trimmed_mean_filter(&img, 1, 2).unwrap(), output);
+
+    cancel.store(true, Ordering::Relaxed);
+    assert!(matches!(filter::median_filter_monitored(&img, 1, None, Some(&cancel)), Err(ImgProcError::Cancelled)));
+    assert!(matches!(filter::alpha_trimmed_mean_filter_monitored(&img, 1, 2, None, Some(&cancel)),
+                     Err(ImgProcError::Cancelled)));
+
+    // A cancelled filter reports no progress
+    let calls = RefCell::new(Vec::new());
+    let result = filter::median_filter_monitored(&img, 1, Some(&|p| calls.borrow_mut().push(p)), Some(&cancel));
+    assert!(matches!(result, Err(ImgProcError::Cancelled)));
+    assert!(calls.into_inner().is_empty());
+}
+
+#[test]
+fn frequency_filter_test() {
+    for &(width, height) in [(8, 8), (6, 10)].iter() {
+        // A checkerboard has all of its energy at the highest frequency, plus a DC offset
+        let mut data = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                data.push(if (x + y) % 2 == 0 { 150.0 } else { 50.0 });
+            }
+        }
+        let img = Image::from_vec(width, height, 1, false, data);
+
+        let lowpass = filter::frequency_filter(&img, FrequencyFilter::Lowpass(0.25), Rolloff::Ideal).unwrap();
+        for val in lowpass.data().iter() {
+            assert!((val - 100.0).abs() < 1e-6);
+        }
+
+        let lowpass = filter::frequency_filter(&img, FrequencyFilter::Lowpass(0.1), Rolloff::Butterworth(2)).unwrap();
+        for val in lowpass.data().iter() {
+            assert!((val - 100.0).abs() < 1.0);
+        }
+
+        let highpass = filter::frequency_filter(&img, FrequencyFilter::Highpass(0.25), Rolloff::Ideal).unwrap();
+        for (val, orig) in highpass.data().iter().zip(img.data().iter()) {
+            assert!((val - (orig - 100.0)).abs() < 1e-6);
+        }
+    }
+
+    let rgb: Image<f64> = noise_image(4, 4, 3, 15).into();
+    assert!(filter::frequency_filter(&rgb, FrequencyFilter::Lowpass(0.25), Rolloff::Ideal).is_err());
+}
+
+#[test]
+fn frequency_filter_non_power_of_two_test() {
+    // A cosine at 0.1 cycles per pixel along x plus a DC offset, with dimensions that are not
+    // powers of two
+    let (width, height) = (30, 21);
+    let mut data = Vec::new();
+    for _ in 0..height {
+        for x in 0..width {
+            data.push(100.0 + 40.0 * (2.0 * std::f64::consts::PI * 0.1 * x as f64).cos());
+        }
+    }
+    let img = Image::from_vec(width, height, 1, false, data);
+
+    let dc = filter::frequency_filter(&img, FrequencyFilter::Lowpass(0.05), Rolloff::Ideal).unwrap();
+    assert!(dc.data().iter().all(|val| (val - 100.0).abs() < 1e-9));
+
+    let all = filter::frequency_filter(&img, FrequencyFilter::Lowpass(0.15), Rolloff::Ideal).unwrap();
+    for (val, orig) in all.data().iter().zip(img.data().iter()) {
+        assert!((val - orig).abs() < 1e-9);
+    }
+
+    let band = filter::frequency_filter(&img, FrequencyFilter::Bandpass(0.05, 0.15), Rolloff::Ideal).unwrap();
+    for (val, orig) in band.data().iter().zip(img.data().iter()) {
+        assert!((val - (orig - 100.0)).abs() < 1e-9);
+    }
+
+    let noise: Image<f64> = noise_image(37, 23, 1, 16).into();
+    let output = filter::frequency_filter(&noise, FrequencyFilter::Lowpass(1.0), Rolloff::Ideal).unwrap();
+    for (val, orig) in output.data().iter().zip(noise.data().iter()) {
+        assert!((val - orig).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn gabor_test() {
+    let (width, height) = (32, 32);
+    let grating = |orientation: f64| {
+        let (sin, cos) = orientation.to_radians().sin_cos();
+        let mut data = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                let t = (x as f64 * cos + y as f64 * sin) * 2.0 * std::f64::consts::PI / 6.0;
+                data.push((127.5 + 100.0 * t.sin()).round() as u8);
+            }
+        }
+
+        Image::from_vec(width, height, 1, false, data)
+    };
+
+    // Mean absolute response away from the edges
+    let response = |img: &Image<f64>| {
+        let mut sum = 0.0;
+        for y in 8..(height - 8) {
+            for x in 8..(width - 8) {
+                sum += img.get_pixel(x, y)[0].abs();
+            }
+        }
+        sum / ((width - 16) * (height - 16)) as f64
+    };
+
+    let matching = filter::gabor(&grating(30.0), 6.0, 30.0, 2.0, 0.5).unwrap();
+    let perpendicular = filter::gabor(&grating(120.0), 6.0, 30.0, 2.0, 0.5).unwrap();
+    assert!(response(&matching) > 10.0 * response(&perpendicular));
+
+    assert!(filter::gabor(&grating(0.0), 0.0, 0.0, 2.0, 0.5).is_err());
+    assert!(filter::gabor(&grating(0.0), 6.0, 0.0, -1.0, 0.5).is_err());
+    assert!(filter::gabor(&grating(0.0), 6.0, 0.0, f64::NAN, 0.5).is_err());
+}
+
+#[test]
+fn apply_in_region_test() {
+    let img = noise_image(40, 30, 3, 7);
+    let roi = Rect::new(10, 8, 20, 12);
+    let blur = |input: &Image<u8>| Ok(filter::gaussian_blur(&input.clone().into(), 5, 1.5)?.into());
+
+    let output = filter::apply_in_region(&img, roi, blur).unwrap();
+    assert_eq!(img.info(), output.info());
+
+    let full: Image<u8> = blur(&img).unwrap();
+    for y in 0..30 {
+        for x in 0..40 {
+            if roi.contains(x, y) {
+                assert_eq!(full.get_pixel(x, y), output.get_pixel(x, y));
+            } else {
+                assert_eq!(img.get_pixel(x, y), output.get_pixel(x, y));
+            }
+        }
+    }
+
+    assert!(filter::apply_in_region(&img, Rect::new(30, 0, 11, 5), blur).is_err());
+    assert!(filter::apply_in_region(&img, Rect::new(0, 0, 0, 5), blur).is_err());
+    assert!(filter::apply_in_region(&img, roi, |input| Ok(input.map_pixels(|p, p_out| p_out.push(p[0])))).is_err());
+}
+
+#[test]
+fn temporal_median_test() {
+    let frame = noise_image(6, 5, 3, 21);
+    let mut outlier = frame.clone();
+    outlier.set_pixel(2, 3, &[255, 255, 255]);
+    let shifted = frame.map_channels(|channel| channel.saturating_add(2));
+
+    // The outlier is replaced by the median of the three frames, and every other pixel is
+    // unchanged
+    let output = filter::temporal_median(&[frame.clone(), outlier, shifted.clone()]).unwrap();
+    let mut expected = frame.clone();
+    expected.set_pixel(2, 3, shifted.get_pixel(2, 3));
+    assert_eq!(expected, output);
+
+    // Even number of frames
+    let a = Image::from_vec(1, 1, 1, false, vec![10u8]);
+    let b = Image::from_vec(1, 1, 1, false, vec![13u8]);
+    assert_eq!(&[12], filter::temporal_median(&[a.clone(), b]).unwrap().data());
+
+    assert!(filter::temporal_median(&[]).is_err());
+    assert!(filter::temporal_median(&[a, frame]).is_err());
+}
+
+#[test]
+fn exposure_fuse_test() {
+    // Horizontal gradient, captured too dark and too bright
+    let mut under = Vec::new();
+    let mut over = Vec::new();
+    for _ in 0..32 {
+        for x in 0..64 {
+            let val = x as f64 * 4.0;
+            under.extend_from_slice(&[(val * 0.4) as u8; 3]);
+            over.extend_from_slice(&[(val * 2.5).min(255.0) as u8; 3]);
+        }
+    }
+    let under = Image::from_vec(64, 32, 3, false, under);
+    let over = Image::from_vec(64, 32, 3, false, over);
+
+    // Mean distance from mid-gray
+    let badness = |img: &Image<u8>| {
+        img.data().iter().map(|val| (*val as f64 / 255.0 - 0.5).abs()).sum::<f64>() / img.data().len() as f64
+    };
+
+    let weights = filter::FusionWeights { contrast: 0.0, saturation: 0.0, well_exposedness: 1.0 };
+    let output = filter::exposure_fuse(&[under.clone(), over.clone()], weights).unwrap();
+    assert_eq!(under.info(), output.info());
+    assert!(badness(&output) < 0.8 * badness(&under));
+    assert!(badness(&output) < 0.8 * badness(&over));
+
+    let output = filter::exposure_fuse(&[under.clone(), over.clone()], filter::FusionWeights::default()).unwrap();
+    assert_eq!(under.info(), output.info());
+
+    assert_eq!(under, filter::exposure_fuse(std::slice::from_ref(&under), weights).unwrap());
+    assert!(filter::exposure_fuse(&[], weights).is_err());
+    assert!(filter::exposure_fuse(&[under, Image::from_vec(1, 1, 3, false, vec![0; 3])], weights).is_err());
+}
+
+#[test]
+fn pixelate_test() {
+    let img = noise_image(7, 5, 3, 14);
+    assert_eq!(img, filter::pixelate(&img, 1).unwrap());
+
+    let img = Image::from_vec(4, 4, 1, false, vec![1, 2, 10, 20,
+                                                   3, 5, 30, 40,
+                                                   0, 0, 7, 7,
+                                                   0, 1, 7, 8]);
+    let output = filter::pixelate(&img, 2).unwrap();
+    assert_eq!(&[3, 3, 25, 25,
+                 3, 3, 25, 25,
+                 0, 0, 7, 7,
+                 0, 0, 7, 7], output.data());
+
+    // Edge blocks are averaged over the pixels they contain
+    let output = filter::pixelate(&img, 3).unwrap();
+    assert_eq!(&[22, 8, 3], &[output.get_pixel(3, 0)[0], output.get_pixel(3, 3)[0], output.get_pixel(0, 3)[0]]);
+
+    assert!(filter::pixelate(&img, 0).is_err());
+}
+
+#[test]
+fn crystallize_test() {
+    let img = Image::from_vec(3, 2, 3, false, vec![0, 10, 20, 30, 40, 50, 60, 70, 80,
+                                                   90, 100, 110, 120, 130, 140, 150, 160, 170]);
+    let output = filter::crystallize(&img, 1, 3).unwrap();
+    assert_eq!(Image::from_color(3, 2, &[75, 85, 95]).unwrap(), output);
+
+    let img = noise_image(20, 16, 3, 15);
+    let output = filter::crystallize(&img, 12, 42).unwrap();
+    assert_eq!(output, filter::crystallize(&img, 12, 42).unwrap());
+    assert_ne!(output, filter::crystallize(&img, 12, 43).unwrap());
+    assert!(analysis::count_colors(&output) <= 12);
+
+    assert!(filter::crystallize(&img, 0, 42).is_err());
+}
+
+#[test]
+fn oil_paint_test() {
+    let flat = Image::from_color(9, 7, &[40u8, 90, 200]).unwrap();
+    assert_eq!(flat, filter::oil_paint(&flat, 2, 8).unwrap());
+
+    // Total absolute difference between horizontally adjacent pixels
+    let variation = |img: &Image<u8>| {
+        let mut sum = 0;
+        for y in 0..img.info().height {
+            for x in 1..img.info().width {
+                for (a, b) in img.get_pixel(x - 1, y).iter().zip(img.get_pixel(x, y).iter()) {
+                    sum += (*a as i32 - *b as i32).abs();
+                }
+            }
+        }
+        sum
+    };
+
+    let img = noise_image(24, 20, 3, 16);
+    let output = filter::oil_paint(&img, 2, 8).unwrap();
+    assert_eq!(img.info(), output.info());
+    assert!(variation(&output) * 2 < variation(&img));
+
+    assert!(filter::oil_paint(&img, 0, 8).is_err());
+    assert!(filter::oil_paint(&img, 2, 0).is_err());
+}
+
+#[test]
+fn cartoonify_test() {
+    // A smooth gradient on the left and a bright flat square on the right
+    let mut data = Vec::new();
+    for y in 0..24 {
+        for x in 0..32 {
+            if x >= 16 && (4..20).contains(&y) {
+                data.extend([230, 220, 210]);
+            } else {
+                data.extend([(x * 4) as u8, (y * 5) as u8, 60]);
+            }
+        }
+    }
+    let img = Image::from_vec(32, 24, 3, false, data);
+    let output = filter::cartoonify(&img, 100.0, 4).unwrap();
+    assert_eq!(img.info(), output.info());
+
+    // Away from edges, each channel takes at most 4 values
+    for c in 0..3 {
+        let mut vals: Vec<u8> = (0..(output.info().size() as usize))
+            .map(|i| output[i][c])
+            .filter(|val| *val != 0)
+            .collect();
+        vals.sort_unstable();
+        vals.dedup();
+        assert!(vals.len() <= 4);
+    }
+
+    // The border of the square is drawn in black, but its interior is not
+    assert_eq!(&[0, 0, 0], output.get_pixel(16, 10));
+    assert_eq!(&[0, 0, 0], output.get_pixel(24, 4));
+    assert_eq!(&[255, 255, 255], output.get_pixel(24, 12));
+
+    assert!(filter::cartoonify(&img, 100.0, 1).is_err());
+}
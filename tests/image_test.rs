@@ -1,4 +1,4 @@
-use imgproc_rs::image::{Image, ImageInfo, SubImage, BaseImage, Pixel};
+use imgproc_rs::image::{Image, ImageInfo, SubImage, BaseImage, Pixel, Rect};
 
 #[test]
 fn image_general_test() {
@@ -86,6 +86,66 @@ fn image_get_subimage_test() {
                img.get_neighborhood_2d(0, 0, 3).data());
 }
 
+#[test]
+fn image_subimage_test() {
+    let img: Image<u8> = Image::from_vec(5, 4, 2, false, (0..40).collect());
+
+    let sub = img.subimage(1, 2, 3, 2).unwrap();
+    assert_eq!(ImageInfo::new(3, 2, 2, false), sub.info());
+    for y in 0..2 {
+        for x in 0..3 {
+            assert_eq!(img.get_pixel(x + 1, y + 2), sub.get_pixel(x, y));
+        }
+    }
+    assert_eq!(vec![22, 23, 24, 25, 26, 27, 32, 33, 34, 35, 36, 37], sub.to_vec());
+
+    assert_eq!(img.data(), &img.subimage(0, 0, 5, 4).unwrap().to_vec()[..]);
+    assert!(img.subimage(3, 0, 3, 1).is_err());
+    assert!(img.subimage(0, 4, 1, 1).is_err());
+}
+
+#[test]
+fn image_tiles_test() {
+    let img: Image<u8> = Image::from_vec(7, 5, 3, false, (0..105).collect());
+
+    for &(tile_width, tile_height) in [(2, 2), (3, 4), (7, 5), (10, 1)].iter() {
+        let tiles = img.tiles(tile_width, tile_height);
+        let count = 7u32.div_ceil(tile_width) * 5u32.div_ceil(tile_height);
+        assert_eq!(count as usize, tiles.size_hint().0);
+
+        let mut output = Image::blank(img.info());
+        let mut n = 0;
+        for (x, y, tile) in tiles {
+            let (width, height) = tile.info().wh();
+            assert!(width <= tile_width && height <= tile_height);
+
+            for j in 0..height {
+                for i in 0..width {
+                    output.set_pixel(x + i, y + j, tile.get_pixel(i, j));
+                }
+            }
+            n += 1;
+        }
+
+        assert_eq!(count, n);
+        assert_eq!(img, output);
+    }
+}
+
+#[test]
+fn image_set_pixel_checked_test() {
+    let mut img: Image<u8> = Image::blank(ImageInfo::new(3, 2, 3, false));
+
+    assert!(img.set_pixel_checked(2, 1, &[1, 2, 3]).is_ok());
+    assert_eq!(&[1, 2, 3], img.get_pixel(2, 1));
+
+    assert!(img.set_pixel_checked(3, 1, &[1, 2, 3]).is_err());
+    assert!(img.set_pixel_checked(0, 2, &[1, 2, 3]).is_err());
+    assert!(img.set_pixel_checked(0, 0, &[1, 2]).is_err());
+    assert!(img.set_pixel_checked(0, 0, &[1, 2, 3, 4]).is_err());
+    assert_eq!(&[0, 0, 0], img.get_pixel(0, 0));
+}
+
 #[test]
 fn image_map_test() {
     let mut img1: Image<u8> = Image::from_slice(2, 2, 4, true,
@@ -130,6 +190,56 @@ fn image_map_test() {
     assert_eq!(&[1, 2, 8, 4, 2, 3, 9, 5, 6, 5, 9, 3, 5, 4, 8, 2], img1.data());
 }
 
+#[test]
+fn image_map_pixels_channel_swap_test() {
+    let img: Image<u8> = Image::from_slice(3, 2, 3, false,
+                                           &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18]);
+
+    // Swap the red and blue channels and drop the green channel
+    let swapped = img.map_pixels(|channels, vec| {
+        vec.push(channels[2]);
+        vec.push(channels[0]);
+    });
+
+    let mut expected = Vec::new();
+    for y in 0..2 {
+        for x in 0..3 {
+            let pixel = img.get_pixel(x, y);
+            expected.push(pixel[2]);
+            expected.push(pixel[0]);
+        }
+    }
+
+    assert_eq!(ImageInfo::new(3, 2, 2, false), swapped.info());
+    assert_eq!(&expected[..], swapped.data());
+}
+
+#[test]
+fn image_zip_map_test() {
+    let img1: Image<u8> = Image::from_slice(2, 2, 2, false, &[1, 8, 3, 4, 9, 2, 0, 7]);
+    let img2: Image<u8> = Image::from_slice(2, 2, 2, false, &[5, 6, 3, 1, 2, 9, 4, 4]);
+
+    let max = img1.zip_map(&img2, |p1, p2, vec| {
+        for (a, b) in p1.iter().zip(p2.iter()) {
+            vec.push(*a.max(b));
+        }
+    }).unwrap();
+    assert_eq!(&[5, 8, 3, 4, 9, 9, 4, 7], max.data());
+
+    let diff: Image<i16> = img1.zip_map(&img2, |p1, p2, vec| {
+        for (a, b) in p1.iter().zip(p2.iter()) {
+            vec.push(*a as i16 - *b as i16);
+        }
+    }).unwrap();
+    assert_eq!(&[-4, 2, 0, 3, 7, -7, -4, 3], diff.data());
+
+    let img3: Image<u8> = Image::from_slice(2, 2, 1, false, &[1, 2, 3, 4]);
+    assert!(img1.zip_map(&img3, |p1, _, vec: &mut Vec<u8>| vec.extend_from_slice(p1)).is_err());
+
+    let empty: Image<u8> = Image::blank(ImageInfo::new(0, 2, 3, false));
+    assert_eq!(empty, empty.zip_map(&empty, |p1, _, vec| vec.extend_from_slice(p1)).unwrap());
+}
+
 #[test]
 fn image_apply_test() {
     let mut img1: Image<u8> = Image::from_slice(2, 2, 4, true,
@@ -179,3 +289,241 @@ fn subimage_test() {
     assert_eq!(pixel, subimg[2]);
     assert_eq!(pixel, subimg.get_pixel(0, 1));
 }
+
+#[test]
+fn rect_test() {
+    let a = Rect::new(1, 1, 4, 3);
+
+    assert!(a.contains(1, 1));
+    assert!(a.contains(4, 3));
+    assert!(!a.contains(5, 3));
+    assert!(!a.contains(4, 4));
+    assert!(!a.contains(0, 2));
+
+    // Overlapping and disjoint rectangles
+    assert_eq!(Some(Rect::new(3, 2, 2, 2)), a.intersect(&Rect::new(3, 2, 6, 6)));
+    assert_eq!(a.intersect(&Rect::new(3, 2, 6, 6)), Rect::new(3, 2, 6, 6).intersect(&a));
+    assert_eq!(Some(a), a.intersect(&Rect::new(0, 0, 10, 10)));
+    assert_eq!(None, a.intersect(&Rect::new(5, 1, 2, 2)));
+    assert_eq!(None, a.intersect(&Rect::new(6, 6, 2, 2)));
+
+    // Rectangles extending past the image edge
+    assert_eq!(Some(Rect::new(3, 2, 2, 3)), Rect::new(3, 2, 10, 10).clip_to(5, 5));
+    assert_eq!(Some(a), a.clip_to(5, 5));
+    assert_eq!(None, Rect::new(5, 0, 2, 2).clip_to(5, 5));
+    assert_eq!(Some(Rect::new(4, 0, 1, 1)), Rect::new(4, 0, u32::MAX, 1).clip_to(5, 5));
+
+    let img = Image::from_vec(5, 4, 1, false, (0..20).collect());
+    assert_eq!(img.subimage(1, 1, 4, 3).unwrap().to_vec(), img.subimage_rect(a).unwrap().to_vec());
+    assert!(img.subimage_rect(Rect::new(3, 2, 10, 10)).is_err());
+}
+
+#[test]
+fn image_from_color_test() {
+    let img = Image::from_color(3, 2, &[10u8, 20, 30]).unwrap();
+    assert_eq!(ImageInfo::new(3, 2, 3, false), img.info());
+    assert!(img.data().chunks(3).all(|p| p == [10, 20, 30]));
+
+    assert!(Image::from_color(2, 2, &[0.5, 1.0]).unwrap().info().alpha);
+    assert!(Image::<u8>::from_color(2, 2, &[]).is_err());
+    assert!(Image::from_color(2, 2, &[1u8, 2, 3, 4, 5]).is_err());
+}
+
+#[test]
+fn image_planar_test() {
+    let img = Image::from_vec(2, 2, 3, false, vec![1u8, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12]);
+    let planar = img.to_planar();
+    assert_eq!(vec![1, 4, 7, 10, 2, 5, 8, 11, 3, 6, 9, 12], planar);
+    assert_eq!(img, Image::from_planar(2, 2, 3, false, &planar).unwrap());
+
+    assert!(Image::from_planar(2, 2, 3, false, &planar[1..]).is_err());
+}
+
+#[test]
+fn image_normalized_tensor_test() {
+    let img = Image::from_vec(2, 1, 3, false, vec![128u8, 128, 128, 255, 255, 255]);
+    let tensor = img.to_normalized_tensor(&[0.5; 3], &[0.5; 3]).unwrap();
+    assert_eq!(6, tensor.len());
+
+    for c in 0..3 {
+        assert!(tensor[2 * c].abs() < 0.01);
+        assert!((tensor[2 * c + 1] - 1.0).abs() < 1e-6);
+    }
+
+    assert!(img.to_normalized_tensor(&[0.5; 2], &[0.5; 3]).is_err());
+    assert!(img.to_normalized_tensor(&[0.5; 3], &[0.5, 0.0, 0.5]).is_err());
+}
+
+#[test]
+fn image_ascii_art_test() {
+    let black: Image<u8> = Image::blank(ImageInfo::new(40, 40, 3, false));
+    let art = black.to_ascii_art(10);
+    assert_eq!(5, art.lines().count());
+    assert!(art.lines().all(|line| line == "@@@@@@@@@@"));
+
+    let white = Image::from_color(40, 20, &[255u8, 255, 255]).unwrap();
+    assert!(white.to_ascii_art(10).lines().all(|line| line == "          "));
+
+    // Half black, half white, with a transparent alpha channel that is ignored
+    let mut data = vec![0u8; 8 * 4 * 2];
+    for (i, p) in data.chunks_mut(2).enumerate() {
+        p[0] = if i % 8 < 4 { 0 } else { 255 };
+    }
+    let split = Image::from_vec(8, 4, 2, true, data);
+    assert_eq!("@@@@    \n@@@@    \n", split.to_ascii_art(20));
+}
+
+#[test]
+fn image_fill_clear_test() {
+    let mut img = Image::from_vec(2, 2, 3, false, (0..12).collect::<Vec<u8>>());
+    img.fill(&[10, 20, 30]).unwrap();
+    assert_eq!(Image::from_color(2, 2, &[10u8, 20, 30]).unwrap(), img);
+    assert!(img.fill(&[10, 20]).is_err());
+
+    img.clear();
+    assert_eq!(Image::blank(img.info()), img);
+}
+
+#[test]
+fn image_linear_gradient_test() {
+    let horz = Image::linear_gradient(5, 2, &[0, 100, 255], &[255, 100, 0], true).unwrap();
+    assert_eq!(ImageInfo::new(5, 2, 3, false), horz.info());
+    for y in 0..2 {
+        assert_eq!(&[0, 100, 255], horz.get_pixel(0, y));
+        assert_eq!(&[128, 100, 128], horz.get_pixel(2, y));
+        assert_eq!(&[255, 100, 0], horz.get_pixel(4, y));
+    }
+
+    let vert = Image::linear_gradient(2, 3, &[0], &[200], false).unwrap();
+    assert_eq!(&[0, 0, 100, 100, 200, 200], vert.data());
+
+    assert!(Image::linear_gradient(2, 2, &[0, 0, 0], &[0, 0], true).is_err());
+}
+
+#[test]
+fn image_map_neighborhood_test() {
+    let img = Image::from_vec(5, 4, 2, false, (0..40).map(|i| (i * 7 % 23) as f64).collect());
+    let radius = 1;
+
+    let output = img.map_neighborhood(radius, |neighborhood| {
+        let mut sum = vec![0.0; 2];
+        for p in neighborhood.iter() {
+            sum[0] += p[0] / 9.0;
+            sum[1] += p[1] / 9.0;
+        }
+        sum
+    });
+
+    // Direct box blur with clamped edges
+    let mut expected = Vec::new();
+    for y in 0..4i32 {
+        for x in 0..5i32 {
+            let mut sum = [0.0; 2];
+            for j in (y - 1)..=(y + 1) {
+                for i in (x - 1)..=(x + 1) {
+                    let p = img.get_pixel(i.clamp(0, 4) as u32, j.clamp(0, 3) as u32);
+                    sum[0] += p[0] / 9.0;
+                    sum[1] += p[1] / 9.0;
+                }
+            }
+            expected.extend_from_slice(&sum);
+        }
+    }
+
+    assert_eq!(img.info(), output.info());
+    assert_eq!(&expected[..], output.data());
+
+    // Radius 0 passes each pixel by itself
+    assert_eq!(img, img.map_neighborhood(0, |neighborhood| neighborhood[0].to_vec()));
+    let first = img.map_neighborhood(2, |neighborhood| neighborhood[0][..1].to_vec());
+    assert_eq!(1, first.info().channels);
+    assert_eq!(&[img.get_pixel(0, 0)[0]], first.get_pixel(1, 1));
+
+    let empty: Image<f64> = Image::blank(ImageInfo::new(0, 3, 2, false));
+    assert_eq!(empty, empty.map_neighborhood(radius, |neighborhood| neighborhood[0].to_vec()));
+}
+
+#[test]
+fn image_map_with_coords_test() {
+    let img = Image::from_vec(5, 2, 3, false, vec![100u8; 30]);
+
+    // Horizontal gradient overlay that adds 0 at the left edge and 100 at the right edge
+    let output = img.map_with_coords(|x, _, p| {
+        p.iter().map(|channel| channel + (x * 100 / 4) as u8).collect()
+    });
+
+    assert_eq!(img.info(), output.info());
+    for y in 0..2 {
+        assert_eq!(&[100, 100, 100], output.get_pixel(0, y));
+        assert_eq!(&[150, 150, 150], output.get_pixel(2, y));
+        assert_eq!(&[200, 200, 200], output.get_pixel(4, y));
+    }
+
+    let coords = img.map_with_coords(|x, y, _| vec![x as u8, y as u8]);
+    assert_eq!(2, coords.info().channels);
+    assert_eq!(&[3, 1], coords.get_pixel(3, 1));
+
+    let empty: Image<u8> = Image::blank(ImageInfo::new(4, 0, 3, false));
+    assert_eq!(empty, empty.map_with_coords(|_, _, p| p.to_vec()));
+}
+
+#[test]
+fn image_scalar_arithmetic_test() {
+    let img = Image::from_vec(2, 1, 2, true, vec![200u8, 150, 10, 150]);
+
+    assert_eq!(&[255, 150, 110, 150], img.saturating_add_scalar(100).data());
+    assert_eq!(&[44, 150, 110, 150], img.wrapping_add_scalar(100).data());
+    assert_eq!(&[0, 150, 0, 150], img.saturating_add_scalar(-300).data());
+    assert_eq!(&[190, 150, 0, 150], img.wrapping_add_scalar(-10).data());
+
+    assert_eq!(&[255, 150, 30, 150], img.saturating_mul_scalar(3).data());
+    assert_eq!(&[88, 150, 30, 150], img.wrapping_mul_scalar(3).data());
+    assert_eq!(&[0, 150, 0, 150], img.saturating_mul_scalar(-1).data());
+}
+
+#[test]
+fn image_approx_eq_test() {
+    let a = Image::from_vec(2, 2, 3, false, (0..12).map(|i| i * 10).collect::<Vec<u8>>());
+    let b = a.map_channels(|channel| if channel % 20 == 0 { channel + 1 } else { channel - 1 });
+
+    assert!(a.approx_eq(&a, 0));
+    assert!(a.approx_eq(&b, 1));
+    assert!(b.approx_eq(&a, 1));
+    assert!(!a.approx_eq(&b, 0));
+
+    let c = Image::from_vec(4, 1, 3, false, a.data().to_vec());
+    assert!(!a.approx_eq(&c, 255));
+
+    let f = Image::from_vec(1, 1, 2, false, vec![0.5, 1.0]);
+    assert!(f.approx_eq(&Image::from_vec(1, 1, 2, false, vec![0.55, 0.98]), 0.1));
+    assert!(!f.approx_eq(&Image::from_vec(1, 1, 2, false, vec![f64::NAN, 1.0]), 0.1));
+}
+
+#[test]
+fn image_bytes_test() {
+    let img = Image::from_vec(4, 3, 3, false, (0..36).collect::<Vec<u8>>());
+
+    let bytes = img.as_bytes();
+    assert_eq!(4 * 3 * 3, bytes.len());
+    assert_eq!(img.get_pixel(2, 1), &bytes[((4 + 2) * 3)..((4 + 2) * 3 + 3)]);
+    assert_eq!(img, Image::from_bytes_with_layout(4, 3, 3, false, bytes).unwrap());
+
+    assert!(Image::from_bytes_with_layout(4, 3, 4, true, bytes).is_err());
+    assert!(Image::from_bytes_with_layout(4, 3, 3, false, &bytes[1..]).is_err());
+}
+
+#[test]
+fn image_from_strided_test() {
+    let expected = Image::from_vec(3, 2, 2, true, (1..=12).collect::<Vec<u8>>());
+
+    // Rows of 6 bytes padded to 8, without padding after the last row
+    let data = [1, 2, 3, 4, 5, 6, 0, 0,
+                7, 8, 9, 10, 11, 12];
+    assert_eq!(expected, Image::from_strided(3, 2, 2, true, &data, 8).unwrap());
+
+    let unpadded: Vec<u8> = (1..=12).collect();
+    assert_eq!(expected, Image::from_strided(3, 2, 2, true, &unpadded, 6).unwrap());
+
+    assert!(Image::from_strided(3, 2, 2, true, &data, 5).is_err());
+    assert!(Image::from_strided(3, 2, 2, true, &data[..13], 8).is_err());
+}
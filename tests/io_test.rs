@@ -0,0 +1,107 @@
+use imgproc_rs::io;
+use imgproc_rs::image::{Image, BaseImage};
+
+use std::fs;
+
+#[test]
+fn process_directory_test() {
+    let dir = std::env::temp_dir().join(format!("imgproc_rs_process_directory_{}", std::process::id()));
+    let in_dir = dir.join("in");
+    let out_dir = dir.join("out");
+    fs::create_dir_all(&in_dir).unwrap();
+
+    let img_1 = Image::from_vec(2, 2, 3, false, (0..12).collect());
+    let img_2 = Image::from_vec(3, 1, 1, false, vec![10, 20, 30]);
+    io::write(&img_1, in_dir.join("a.png").to_str().unwrap()).unwrap();
+    io::write(&img_2, in_dir.join("b.png").to_str().unwrap()).unwrap();
+    fs::write(in_dir.join("c.png"), b"not an image").unwrap();
+    fs::write(in_dir.join("notes.txt"), b"not an image either").unwrap();
+
+    let results = io::process_directory(in_dir.to_str().unwrap(), out_dir.to_str().unwrap(), |img| {
+        Ok(img.map_channels(|channel| 255 - channel))
+    }).unwrap();
+
+    assert_eq!(3, results.len());
+    assert_eq!("a.png", results[0].0);
+    assert!(results[0].1.is_ok());
+    assert_eq!("b.png", results[1].0);
+    assert!(results[1].1.is_ok());
+    assert_eq!("c.png", results[2].0);
+    assert!(results[2].1.is_err());
+
+    let output = io::read(out_dir.join("b.png").to_str().unwrap()).unwrap();
+    assert_eq!((3, 1), output.info().wh());
+    assert_eq!(&[245, 235, 225], output.data());
+    assert!(!out_dir.join("c.png").exists());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn read_u16_test() {
+    let dir = std::env::temp_dir().join(format!("imgproc_rs_read_u16_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+
+    let data: Vec<u16> = (0..12).map(|i| i * 5000 + 7).collect();
+    let buf: image::ImageBuffer<image::Luma<u16>, Vec<u16>> = image::ImageBuffer::from_raw(4, 3, data.clone()).unwrap();
+
+    for name in ["depth.png", "depth.tiff"].iter() {
+        let path = dir.join(name);
+        buf.save(&path).unwrap();
+
+        let img = io::read_u16(path.to_str().unwrap()).unwrap();
+        assert_eq!((4, 3, 1, false), img.info().whca());
+        assert_eq!(&data[..], img.data());
+        assert_eq!(data.iter().map(|val| 65535 - val).collect::<Vec<u16>>(),
+                   img.map_channels(|channel| 65535 - channel).data());
+    }
+
+    // 8-bit images are scaled to 16 bits
+    let path = dir.join("gray.png");
+    io::write(&Image::from_vec(2, 1, 1, false, vec![0u8, 255]), path.to_str().unwrap()).unwrap();
+    assert_eq!(&[0, 65535], io::read_u16(path.to_str().unwrap()).unwrap().data());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn png_indexed_test() {
+    let dir = std::env::temp_dir().join(format!("imgproc_rs_png_indexed_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("indexed.png");
+    let filename = path.to_str().unwrap();
+
+    let palette = [[255, 0, 0], [0, 128, 255], [20, 20, 20]];
+    let indices = Image::from_vec(4, 2, 1, false, vec![0, 1, 2, 1, 2, 2, 0, 1]);
+    io::encode_png_indexed(&indices, &palette, filename).unwrap();
+
+    let (output, output_palette) = io::read_png_indexed(filename).unwrap();
+    assert_eq!(indices, output);
+    assert_eq!(&palette[..], &output_palette[..]);
+
+    // Decoding normally expands the indices into colors
+    let colors = io::read(filename).unwrap();
+    assert_eq!((4, 2, 3, false), colors.info().whca());
+    for i in 0..8 {
+        assert_eq!(&palette[indices[i][0] as usize], &colors[i]);
+    }
+
+    assert!(io::read_png_indexed(dir.join("missing.png").to_str().unwrap()).is_err());
+    assert!(io::encode_png_indexed(&indices, &palette[..2], filename).is_err());
+    assert!(io::encode_png_indexed(&indices, &[], filename).is_err());
+    assert!(io::encode_png_indexed(&indices, &[[0, 0, 0]; 257], filename).is_err());
+    assert!(io::encode_png_indexed(&Image::from_vec(1, 1, 3, false, vec![0, 0, 0]), &palette, filename).is_err());
+
+    // A PNG with 2-bit indices
+    let file = fs::File::create(dir.join("packed.png")).unwrap();
+    let mut encoder = png::Encoder::new(file, 5, 1);
+    encoder.set_color(png::ColorType::Indexed);
+    encoder.set_depth(png::BitDepth::Two);
+    encoder.set_palette(palette.concat());
+    encoder.write_header().unwrap().write_image_data(&[0b00_01_10_01, 0b10_000000]).unwrap();
+
+    let (output, _) = io::read_png_indexed(dir.join("packed.png").to_str().unwrap()).unwrap();
+    assert_eq!(&[0, 1, 2, 1, 2], output.data());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
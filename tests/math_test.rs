@@ -1,6 +1,7 @@
 use imgproc_rs::util;
-use imgproc_rs::util::constants::K_GAUSSIAN_BLUR_2D_3;
+use imgproc_rs::util::constants::{K_GAUSSIAN_BLUR_2D_3, K_SHARPEN};
 use imgproc_rs::image::SubImage;
+use imgproc_rs::error::ImgProcError;
 
 #[test]
 fn vector_mul_test() {
@@ -15,6 +16,78 @@ fn vector_mul_test() {
     assert_eq!(vec![14, 32, 50], res2);
 }
 
+#[test]
+fn vector_mul_invalid_test() {
+    let mut res = Vec::new();
+
+    // Empty vector
+    assert!(util::vector_mul(&[1, 2, 3, 4], &[]).is_err());
+    assert!(util::vector_mul_mut(&[1, 2, 3, 4], &[], &mut res).is_err());
+
+    // Matrix length is not a multiple of the vector length
+    assert!(util::vector_mul(&[1, 2, 3, 4, 5], &[1, 2]).is_err());
+    assert!(util::vector_mul_mut(&[1, 2, 3, 4, 5], &[1, 2], &mut res).is_err());
+
+    // Matrix is not square
+    assert!(util::vector_mul(&[1, 2, 3, 4, 5, 6], &[1, 2]).is_err());
+}
+
+#[test]
+fn separate_kernel_test() {
+    // Separable kernel
+    let (vert, horz) = util::separate_kernel(&K_GAUSSIAN_BLUR_2D_3).unwrap().unwrap();
+    assert_eq!(3, vert.len());
+    assert_eq!(3, horz.len());
+    for y in 0..3 {
+        for x in 0..3 {
+            assert!((vert[y] * horz[x] - K_GAUSSIAN_BLUR_2D_3[y * 3 + x]).abs() < 1e-12);
+        }
+    }
+
+    // Unseparable kernel
+    assert!(util::separate_kernel(&K_SHARPEN).unwrap().is_none());
+
+    // Non-square kernel length
+    assert!(util::separate_kernel(&[1.0; 8]).is_err());
+
+    // Empty kernel
+    assert!(util::separate_kernel(&[]).is_err());
+}
+
+#[test]
+fn kernel_test() {
+    let box_blur = util::Kernel::box_blur(3).unwrap();
+    assert_eq!(3, box_blur.size());
+    assert!((box_blur.data().iter().sum::<f64>() - 1.0).abs() < 1e-12);
+
+    let gaussian = util::Kernel::gaussian(5, 1.0).unwrap();
+    assert_eq!(25, gaussian.data().len());
+    assert!((gaussian.data().iter().sum::<f64>() - 1.0).abs() < 1e-12);
+
+    assert_eq!(&[-1.0, 0.0, 1.0, -2.0, 0.0, 2.0, -1.0, 0.0, 1.0], util::Kernel::sobel_x().data());
+    assert_eq!(&K_SHARPEN, util::Kernel::sharpen().data());
+
+    assert!(util::Kernel::box_blur(4).is_err());
+    assert!(util::Kernel::new(3, vec![1.0; 8]).is_err());
+}
+
+#[test]
+fn ciede2000_test() {
+    // Reference pairs from Sharma, Wu, and Dalal's CIEDE2000 test data
+    let pairs = [
+        ([50.0, 2.6772, -79.7751], [50.0, 0.0, -82.7485], 2.0425),
+        ([50.0, 2.49, -0.001], [50.0, -2.49, 0.0009], 7.1792),
+        ([50.0, 0.0, 0.0], [50.0, -1.0, 2.0], 2.3669),
+        ([50.0, 2.5, 0.0], [73.0, 25.0, -18.0], 27.1492),
+        ([60.2574, -34.0099, 36.2677], [60.4626, -34.1751, 39.4387], 1.2644),
+    ];
+
+    for (lab_1, lab_2, expected) in pairs.iter() {
+        assert!((util::ciede2000_fn(lab_1, lab_2) - expected).abs() < 1e-4);
+        assert!((util::ciede2000_fn(lab_2, lab_1) - expected).abs() < 1e-4);
+    }
+}
+
 #[test]
 fn max_test() {
     // Test max_3()
@@ -74,6 +147,35 @@ fn apply_1d_kernel_test() {
     assert_eq!(vec![11.0, 15.0, 19.0], res);
 }
 
+#[test]
+#[cfg(not(feature = "rayon"))]
+fn apply_1d_kernel_invalid_test() {
+    let pixels: Vec<&[f64]> = vec![&[1.0], &[2.0], &[3.0], &[4.0]];
+    let mut res = Vec::new();
+
+    // Even-length kernel
+    let subimg = SubImage::new(4, 1, 1, false, pixels.clone());
+    assert!(util::apply_1d_kernel(&subimg, &mut res, &[1.0, 2.0, 2.0, 1.0]).is_err());
+
+    // Kernel and pixel dimensions do not match
+    let subimg = SubImage::new(3, 1, 1, false, pixels[..3].to_vec());
+    assert!(util::apply_1d_kernel(&subimg, &mut res, &[1.0, 2.0, 3.0, 2.0, 1.0]).is_err());
+}
+
+#[test]
+#[cfg(feature = "rayon")]
+fn apply_1d_kernel_invalid_test() {
+    let pixels: Vec<&[f64]> = vec![&[1.0], &[2.0], &[3.0], &[4.0]];
+
+    // Even-length kernel
+    let subimg = SubImage::new(4, 1, 1, false, pixels.clone());
+    assert!(util::apply_1d_kernel(&subimg, &[1.0, 2.0, 2.0, 1.0]).is_err());
+
+    // Kernel and pixel dimensions do not match
+    let subimg = SubImage::new(3, 1, 1, false, pixels[..3].to_vec());
+    assert!(util::apply_1d_kernel(&subimg, &[1.0, 2.0, 3.0, 2.0, 1.0]).is_err());
+}
+
 #[test]
 #[cfg(not(feature = "rayon"))]
 fn apply_2d_kernel_test() {
@@ -113,3 +215,36 @@ fn apply_2d_kernel_test() {
 
     assert_eq!(vec![3.5625, 3.8125, 4.0625], res);
 }
+
+#[test]
+#[cfg(not(feature = "rayon"))]
+fn apply_kernel_dimension_mismatch_test() {
+    let pixels: Vec<&[f64]> = vec![&[1.0]; 9];
+    let mut res = Vec::new();
+
+    let subimg = SubImage::new(3, 1, 1, false, pixels[..3].to_vec());
+    let err = util::apply_1d_kernel(&subimg, &mut res, &[1.0; 5]).unwrap_err();
+    assert!(matches!(err, ImgProcError::DimensionMismatch { expected: (5, 1), found: (3, 1) }));
+    assert_eq!("dimension mismatch: expected 5x1, found 3x1", err.to_string());
+
+    let subimg = SubImage::new(3, 3, 1, false, pixels);
+    let err = util::apply_2d_kernel(&subimg, &mut res, &[1.0; 25]).unwrap_err();
+    assert!(matches!(err, ImgProcError::DimensionMismatch { expected: (5, 5), found: (3, 3) }));
+    assert_eq!("dimension mismatch: expected 5x5, found 3x3", err.to_string());
+}
+
+#[test]
+#[cfg(feature = "rayon")]
+fn apply_kernel_dimension_mismatch_test() {
+    let pixels: Vec<&[f64]> = vec![&[1.0]; 9];
+
+    let subimg = SubImage::new(1, 3, 1, false, pixels[..3].to_vec());
+    let err = util::apply_1d_kernel(&subimg, &[1.0; 5]).unwrap_err();
+    assert!(matches!(err, ImgProcError::DimensionMismatch { expected: (1, 5), found: (1, 3) }));
+    assert_eq!("dimension mismatch: expected 1x5, found 1x3", err.to_string());
+
+    let subimg = SubImage::new(3, 3, 1, false, pixels);
+    let err = util::apply_2d_kernel(&subimg, &[1.0; 25]).unwrap_err();
+    assert!(matches!(err, ImgProcError::DimensionMismatch { expected: (5, 5), found: (3, 3) }));
+    assert_eq!("dimension mismatch: expected 5x5, found 3x3", err.to_string());
+}
@@ -0,0 +1,36 @@
+#![allow(dead_code)]
+
+mod common;
+
+use common::noise_image;
+use imgproc_rs::tone;
+use imgproc_rs::enums::{Scale, Tone};
+use imgproc_rs::image::BaseImage;
+use imgproc_rs::pipeline::ImagePipeline;
+
+#[test]
+fn pipeline_test() {
+    let img = noise_image(8, 6, 3, 5);
+
+    let expected = tone::contrast(&tone::brightness(&img, 30, Tone::Rgb).unwrap(), 0.8, Tone::Rgb).unwrap();
+    let output = ImagePipeline::new(img.clone())
+        .brightness(30, Tone::Rgb)
+        .contrast(0.8, Tone::Rgb)
+        .build()
+        .unwrap();
+    assert_eq!(expected, output);
+
+    let resized = ImagePipeline::new(img.clone())
+        .blur(1.0)
+        .resize(4, 3, Scale::Bilinear)
+        .build()
+        .unwrap();
+    assert_eq!((4, 3), resized.info().wh());
+
+    // The first error is returned and later operations are skipped
+    let result = ImagePipeline::new(img)
+        .brightness(300, Tone::Rgb)
+        .then(|_| panic!("operation after an error should not run"))
+        .build();
+    assert!(result.is_err());
+}
@@ -0,0 +1,78 @@
+use imgproc_rs::segmentation;
+use imgproc_rs::error::ImgProcError;
+use imgproc_rs::image::{BaseImage, Image};
+
+#[test]
+fn watershed_test() {
+    // Two overlapping discs of radius 9 centered at (12, 10) and (28, 10)
+    let in_disc = |x: u32, y: u32, c_x: f64| (x as f64 - c_x).hypot(y as f64 - 10.0) <= 9.0;
+    let blobs = Image::from_vec(40, 20, 1, false, (0..800).map(|i| {
+        if in_disc(i % 40, i / 40, 12.0) || in_disc(i % 40, i / 40, 28.0) { 255 } else { 0 }
+    }).collect());
+
+    let mut markers: Image<u32> = Image::from_vec(40, 20, 1, false, vec![0; 800]);
+    markers.set_pixel(12, 10, &[1]);
+    markers.set_pixel(28, 10, &[2]);
+    markers.set_pixel(0, 0, &[3]);
+
+    let labels = segmentation::watershed(&blobs, &markers).unwrap();
+    assert_eq!((40, 20, 1), labels.info().whc());
+
+    for y in 0..20 {
+        for x in 0..40 {
+            if in_disc(x, y, 12.0) && x <= 18 && (x as f64 - 12.0).hypot(y as f64 - 10.0) <= 7.0 {
+                assert_eq!(1, labels.get_pixel(x, y)[0]);
+            } else if in_disc(x, y, 28.0) && x >= 22 && (x as f64 - 28.0).hypot(y as f64 - 10.0) <= 7.0 {
+                assert_eq!(2, labels.get_pixel(x, y)[0]);
+            }
+        }
+    }
+
+    // The line between the blobs runs through the neck where they touch
+    for y in 7..14 {
+        assert!((19..=21).any(|x| labels.get_pixel(x, y)[0] == 0));
+        assert!((17..=19).all(|x| labels.get_pixel(x, y)[0] != 2));
+        assert!((21..=23).all(|x| labels.get_pixel(x, y)[0] != 1));
+    }
+
+    let small: Image<u32> = Image::from_vec(39, 20, 1, false, vec![0; 780]);
+    assert!(matches!(segmentation::watershed(&blobs, &small), Err(ImgProcError::DimensionMismatch { .. })));
+}
+
+#[test]
+fn slic_test() {
+    // Four color regions with boundaries that do not line up with the superpixel grid
+    let color = |x: u32, y: u32| match (x < 21, y < 27) {
+        (true, true) => [200, 30, 30],
+        (false, true) => [30, 180, 40],
+        (true, false) => [40, 40, 210],
+        (false, false) => [240, 240, 240],
+    };
+    let img = Image::from_vec(48, 40, 3, false, (0..(48 * 40))
+        .flat_map(|i| color(i % 48, i / 48).to_vec())
+        .collect());
+
+    let labels = segmentation::slic(&img, 8, 10.0).unwrap();
+    assert_eq!((48, 40, 1), labels.info().whc());
+
+    let num_labels = *labels.data().iter().max().unwrap() as usize + 1;
+    assert!(num_labels >= 20);
+
+    // Count the pixels of each color in each superpixel
+    let mut counts = vec![[0; 4]; num_labels];
+    for y in 0..40 {
+        for x in 0..48 {
+            let region = (x >= 21) as usize + 2 * (y >= 27) as usize;
+            counts[labels.get_pixel(x, y)[0] as usize][region] += 1;
+        }
+    }
+
+    for count in counts.iter() {
+        let total: u32 = count.iter().sum();
+        let max = *count.iter().max().unwrap();
+        assert!(max as f64 >= 0.95 * total as f64);
+    }
+
+    assert!(segmentation::slic(&img, 1, 10.0).is_err());
+    assert!(segmentation::slic(&Image::from_vec(2, 2, 1, false, vec![0; 4]), 8, 10.0).is_err());
+}
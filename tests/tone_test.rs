@@ -2,8 +2,9 @@
 
 mod common;
 
-use common::setup;
-use imgproc_rs::tone;
+use common::{setup, noise_image};
+use imgproc_rs::{colorspace, tone};
+use imgproc_rs::image::{BaseImage, Image};
 use imgproc_rs::io::write;
 
 use std::time::SystemTime;
@@ -75,3 +76,286 @@ fn histogram_equalization_test() {
 
     write(&proc, "images/tests/tone/histogram.png").unwrap();
 }
+
+#[test]
+fn brightness_contrast_lab_test() {
+    // Keep colors away from the edges of the gamut so that the intermediate image is not clipped
+    let img = noise_image(8, 6, 3, 12).map_channels(|channel| 60 + channel / 3);
+
+    let lab = colorspace::srgb_to_lab(&img, &White::D50);
+    let adjusted = tone::contrast_lab(&tone::brightness_lab(&lab, 10).unwrap(), 0.8).unwrap();
+    let once = colorspace::lab_to_srgb(&adjusted, &White::D50);
+
+    let brightened = tone::brightness(&img, 10, Tone::Lab).unwrap();
+    let twice = tone::contrast(&brightened, 0.8, Tone::Lab).unwrap();
+
+    for (a, b) in once.data().iter().zip(twice.data().iter()) {
+        assert!((*a as i32 - *b as i32).abs() <= 1);
+    }
+
+    assert!(tone::brightness_lab(&lab, 256).is_err());
+    assert!(tone::contrast_lab(&lab, -1.0).is_err());
+}
+
+#[test]
+fn tone_mut_test() {
+    let img = noise_image(8, 6, 4, 11);
+
+    let mut output = img.clone();
+    tone::brightness_mut(&mut output, 40, Tone::Rgb).unwrap();
+    assert_eq!(tone::brightness(&img, 40, Tone::Rgb).unwrap(), output);
+
+    let mut output = img.clone();
+    tone::contrast_mut(&mut output, 1.5, Tone::Rgb).unwrap();
+    assert_eq!(tone::contrast(&img, 1.5, Tone::Rgb).unwrap(), output);
+
+    let rgb = noise_image(8, 6, 3, 11);
+    let mut output = rgb.clone();
+    tone::contrast_mut(&mut output, 0.5, Tone::Lab).unwrap();
+    assert_eq!(tone::contrast(&rgb, 0.5, Tone::Lab).unwrap(), output);
+
+    let mut output = img.clone();
+    tone::gamma_mut(&mut output, 2.2, 255).unwrap();
+    assert_eq!(tone::gamma(&img, 2.2, 255).unwrap(), output);
+
+    let mut output = img.clone();
+    assert!(tone::brightness_mut(&mut output, 300, Tone::Rgb).is_err());
+    assert_eq!(img, output);
+}
+
+#[test]
+fn flatten_illumination_test() {
+    // A flat gray target lit from the left, with a darker square in the middle
+    let mut data = Vec::new();
+    for y in 0..32 {
+        for x in 0..48 {
+            let shading = 1.0 - 0.6 * x as f64 / 47.0;
+            let target = if (12..20).contains(&y) && (20..28).contains(&x) { 150.0 } else { 200.0 };
+            data.push((target * shading).round() as u8);
+        }
+    }
+    let img = Image::from_vec(48, 32, 1, false, data);
+
+    let bg = tone::estimate_background(&img, 8).unwrap();
+    assert_eq!(img.info(), bg.info());
+    assert!(bg.get_pixel(0, 0)[0] > bg.get_pixel(47, 0)[0]);
+
+    let range = |img: &Image<u8>, y: u32| {
+        let row: Vec<u8> = (0..48).map(|x| img.get_pixel(x, y)[0]).collect();
+        row.iter().max().unwrap() - row.iter().min().unwrap()
+    };
+
+    let output = tone::flatten_illumination(&img, 8).unwrap();
+    assert!(range(&output, 2) * 4 < range(&img, 2));
+
+    assert!(tone::estimate_background(&img, 0).is_err());
+}
+
+#[test]
+fn single_scale_retinex_test() {
+    // A bright background with a dark, faintly textured foreground on the left
+    let mut data = Vec::new();
+    for y in 0..32 {
+        for x in 0..32 {
+            data.push(if x < 12 { 10 + ((x + y) % 2) * 6 } else { 240 });
+        }
+    }
+    let img = Image::from_vec(32, 32, 1, false, data);
+
+    let variance = |img: &Image<u8>| {
+        let vals: Vec<f64> = (4..28).flat_map(|y| (2..10).map(move |x| (x, y)))
+            .map(|(x, y)| img.get_pixel(x, y)[0] as f64)
+            .collect();
+        let mean = vals.iter().sum::<f64>() / vals.len() as f64;
+        vals.iter().map(|val| (val - mean).powi(2)).sum::<f64>() / vals.len() as f64
+    };
+
+    let output = tone::single_scale_retinex(&img, 4.0).unwrap();
+    assert_eq!(img.info(), output.info());
+    assert!(variance(&output) > 4.0 * variance(&img));
+
+    assert!(tone::single_scale_retinex(&img, 0.0).is_err());
+}
+
+#[test]
+fn reinhard_tonemap_test() {
+    let vals = [0.0, 0.01, 0.1, 0.5, 1.0, 2.0, 5.0, 10.0, 50.0, 1000.0];
+
+    let gray = Image::from_slice(10, 1, 1, false, &vals);
+    let output = tone::reinhard_tonemap(&gray);
+    assert_eq!(0, output.data()[0]);
+    assert_eq!(255, output.data()[9]);
+    assert!(output.data().windows(2).all(|pair| pair[0] < pair[1]));
+
+    // Color is preserved and alpha is left unchanged
+    let rgba = Image::from_slice(2, 1, 4, true, &[200.0, 100.0, 0.0, 255.0, 2.0, 2.0, 2.0, 128.0]);
+    let output = tone::reinhard_tonemap(&rgba);
+    let p = output.get_pixel(0, 0);
+    assert!(p[0] > p[1] && p[1] > p[2] && p[2] == 0);
+    assert_eq!(255, p[3]);
+    assert_eq!(&[170, 170, 170, 128], output.get_pixel(1, 0));
+}
+
+#[test]
+fn grayscale_tone_test() {
+    let gray = Image::from_vec(4, 2, 1, false, vec![0, 30, 60, 90, 120, 150, 180, 210]);
+
+    let output = tone::brightness(&gray, 20, Tone::Lab).unwrap();
+    assert_eq!(gray.info(), output.info());
+    for (before, after) in gray.data().iter().zip(output.data().iter()) {
+        assert!(after > before);
+    }
+
+    let output = tone::contrast(&gray, 1.2, Tone::Lab).unwrap();
+    assert_eq!(gray.info(), output.info());
+    assert!(output.get_pixel(3, 1)[0] > 210);
+
+    let output = tone::histogram_equalization(&gray, 0.5, &White::D65, 255.0).unwrap();
+    assert_eq!(gray.info(), output.info());
+
+    assert_eq!(gray, tone::saturation(&gray, 50).unwrap());
+
+    // Alpha is kept alongside the single gray channel
+    let gray_alpha = Image::from_vec(2, 1, 2, true, vec![100, 7, 200, 9]);
+    let output = tone::brightness(&gray_alpha, 20, Tone::Lab).unwrap();
+    assert_eq!(gray_alpha.info(), output.info());
+    assert_eq!(&[7, 9], &[output[0][1], output[1][1]]);
+}
+
+#[test]
+fn histogram_equalization_alpha_test() {
+    let noise = noise_image(16, 12, 4, 21);
+    let img = Image::from_vec(16, 12, 4, true, noise.data().to_vec());
+    let output = tone::histogram_equalization(&img, 1.0, &White::D65, 255.0).unwrap();
+    assert_eq!(img.info(), output.info());
+
+    let mut color_changed = false;
+    for i in 0..(img.info().size() as usize) {
+        assert_eq!(img[i][3], output[i][3]);
+        color_changed |= img[i][..3] != output[i][..3];
+    }
+    assert!(color_changed);
+}
+
+#[test]
+fn srgb_encode_test() {
+    let img = Image::from_vec(256, 1, 1, false, (0..=255).collect());
+    let encoded = tone::srgb_encode(&img);
+    let round_trip = tone::srgb_decode(&encoded);
+
+    assert_eq!(0, encoded[0][0]);
+    assert_eq!(255, encoded[255][0]);
+    assert!(encoded[128][0] > 128);
+    for i in 0..256 {
+        assert!((round_trip[i][0] as i32 - i as i32).abs() <= 1);
+    }
+}
+
+// Computes the normalized cumulative histogram of channel `c` of `img`
+fn channel_cdf(img: &Image<u8>, c: usize) -> Vec<f64> {
+    let mut cdf = vec![0.0; 256];
+    for i in 0..(img.info().size() as usize) {
+        cdf[img[i][c] as usize] += 1.0;
+    }
+
+    let mut sum = 0.0;
+    for val in cdf.iter_mut() {
+        sum += *val;
+        *val = sum / img.info().size() as f64;
+    }
+
+    cdf
+}
+
+#[test]
+fn histogram_match_test() {
+    let img = noise_image(64, 64, 3, 30);
+    assert_eq!(img, tone::histogram_match(&img, &img).unwrap());
+
+    let reference = tone::gamma(&noise_image(48, 40, 3, 31), 2.5, 255).unwrap();
+    let output = tone::histogram_match(&img, &reference).unwrap();
+    assert_eq!(img.info(), output.info());
+
+    for c in 0..3 {
+        let cdf_out = channel_cdf(&output, c);
+        let cdf_ref = channel_cdf(&reference, c);
+        for (o, r) in cdf_out.iter().zip(cdf_ref.iter()) {
+            assert!((o - r).abs() < 0.02);
+        }
+    }
+
+    assert!(tone::histogram_match(&img, &noise_image(4, 4, 1, 0)).is_err());
+}
+
+#[test]
+fn contrast_pivot_test() {
+    let img = Image::from_vec(4, 1, 1, false, vec![128, 64, 192, 100]);
+    let output = tone::contrast_pivot(&img, 2.0, 128).unwrap();
+    assert_eq!(&[128, 0, 255, 72], output.data());
+
+    assert_eq!(img, tone::contrast_pivot(&img, 1.0, 128).unwrap());
+    assert_eq!(tone::contrast(&img, 0.5, Tone::Rgb).unwrap(), tone::contrast_pivot(&img, 0.5, 0).unwrap());
+    assert!(tone::contrast_pivot(&img, -1.0, 128).is_err());
+}
+
+// Returns the mean of each channel and the mean saturation ((max - min) / max) of an RGB image
+fn color_stats(img: &Image<u8>) -> ([f64; 3], f64) {
+    let size = img.info().size() as usize;
+    let mut means = [0.0; 3];
+    let mut saturation = 0.0;
+
+    for i in 0..size {
+        let p = &img[i];
+        for c in 0..3 {
+            means[c] += p[c] as f64 / size as f64;
+        }
+
+        let max = *p.iter().max().unwrap() as f64;
+        let min = *p.iter().min().unwrap() as f64;
+        if max > 0.0 {
+            saturation += (max - min) / max / size as f64;
+        }
+    }
+
+    (means, saturation)
+}
+
+#[test]
+fn msrcr_test() {
+    // Flat patches of saturated color under a strong yellow cast
+    let colors = [[200.0, 40.0, 40.0], [40.0, 200.0, 40.0], [40.0, 40.0, 200.0], [200.0, 200.0, 40.0]];
+    let mut data = Vec::new();
+    for y in 0..48 {
+        for x in 0..48 {
+            let color = colors[(y / 24) * 2 + x / 24];
+            data.extend([color[0] as u8, (color[1] * 0.7) as u8, (color[2] * 0.3) as u8]);
+        }
+    }
+    let img = Image::from_vec(48, 48, 3, false, data);
+
+    let msrcr = tone::msrcr(&img, &[2.0, 8.0, 20.0]).unwrap();
+    let ssr = tone::single_scale_retinex(&img, 8.0).unwrap();
+    assert_eq!(img.info(), msrcr.info());
+
+    let spread = |means: [f64; 3]| means.iter().cloned().fold(f64::MIN, f64::max)
+        - means.iter().cloned().fold(f64::MAX, f64::min);
+    let (img_means, _) = color_stats(&img);
+    let (msrcr_means, msrcr_saturation) = color_stats(&msrcr);
+    let (_, ssr_saturation) = color_stats(&ssr);
+    assert!(spread(msrcr_means) < spread(img_means));
+    assert!(msrcr_saturation > ssr_saturation);
+
+    let rgba = Image::from_vec(48, 48, 4, true, img.data().chunks(3)
+        .flat_map(|p| [p[0], p[1], p[2], 77])
+        .collect());
+    let output = tone::msrcr(&rgba, &[2.0, 8.0, 20.0]).unwrap();
+    for i in 0..(output.info().size() as usize) {
+        for c in 0..3 {
+            assert!((msrcr[i][c] as i32 - output[i][c] as i32).abs() <= 1);
+        }
+        assert_eq!(77, output[i][3]);
+    }
+
+    assert!(tone::msrcr(&img, &[]).is_err());
+    assert!(tone::msrcr(&img, &[2.0, 0.0]).is_err());
+}
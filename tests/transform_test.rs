@@ -2,13 +2,13 @@
 
 mod common;
 
-use common::setup;
+use common::{setup, noise_image};
 use imgproc_rs::transform;
-use imgproc_rs::image::{BaseImage, Image};
+use imgproc_rs::image::{BaseImage, Image, ImageInfo};
 use imgproc_rs::io::write;
 
 use std::time::SystemTime;
-use imgproc_rs::enums::{Scale, Refl};
+use imgproc_rs::enums::{Anchor, Orientation, Scale, Refl};
 
 const PATH: &str = "images/beach.jpg";
 
@@ -174,3 +174,555 @@ fn shear() {
     write(&output_pn.into(), "images/tests/transform/shear_py.png").unwrap();
     write(&output_nn.into(), "images/tests/transform/shear_ny.png").unwrap();
 }
+
+fn checkerboard(width: u32, height: u32) -> Image<u8> {
+    let mut data = Vec::with_capacity((width * height) as usize);
+    for y in 0..height {
+        for x in 0..width {
+            data.push(if (x + y) % 2 == 0 { 0 } else { 255 });
+        }
+    }
+
+    Image::from_vec(width, height, 1, false, data)
+}
+
+#[test]
+fn resize_test() {
+    let img: Image<f64> = Image::from_slice(2, 2, 1, false, &[1.0, 2.0, 3.0, 4.0]);
+
+    assert_eq!(img, transform::resize(&img, 2, 2, Scale::Bilinear).unwrap());
+    assert_eq!(img, transform::resize(&img, 2, 2, Scale::Lanczos).unwrap());
+
+    let nearest = transform::resize(&img, 4, 4, Scale::NearestNeighbor).unwrap();
+    assert_eq!((4, 4), nearest.info().wh());
+    assert_eq!(&[1.0, 1.0, 2.0, 2.0], &nearest.data()[0..4]);
+    assert_eq!(&[3.0, 3.0, 4.0, 4.0], &nearest.data()[12..16]);
+
+    assert!(transform::resize(&img, 0, 2, Scale::Bilinear).is_err());
+
+    let empty: Image<f64> = Image::blank(ImageInfo::new(0, 0, 1, false));
+    assert!(transform::resize(&empty, 2, 2, Scale::Bilinear).is_err());
+}
+
+#[test]
+fn resize_downscale_test() {
+    // A single bright pixel far from the output pixel center
+    let mut data = vec![0.0; 8];
+    data[0] = 255.0;
+    let img = Image::from_vec(8, 1, 1, false, data);
+
+    // Every input pixel contributes when downscaling by more than the kernel support
+    for method in [Scale::Bilinear, Scale::Bicubic, Scale::Lanczos] {
+        let output = transform::resize(&img, 1, 1, method).unwrap();
+        assert!(output[0][0] > 0.0);
+    }
+
+    let constant = Image::from_vec(12, 9, 1, false, vec![100.0; 108]);
+    let output = transform::resize(&constant, 2, 2, Scale::Lanczos).unwrap();
+    assert!(output.data().iter().all(|val| (val - 100.0).abs() < 1e-9));
+}
+
+#[test]
+fn resize_triangle_test() {
+    let img: Image<f64> = noise_image(12, 12, 3, 8).into();
+
+    for size in [6, 4, 3] {
+        let triangle = transform::resize(&img, size, size, Scale::Triangle).unwrap();
+        let bilinear = transform::resize(&img, size, size, Scale::Bilinear).unwrap();
+        assert_eq!(bilinear, triangle);
+    }
+
+    // Direct tent filter whose half-width is the downscale ratio, with clamped edges
+    let row: Image<f64> = noise_image(10, 1, 1, 18).into();
+    let ratio = 10.0 / 4.0;
+    let output = transform::resize(&row, 4, 1, Scale::Triangle).unwrap();
+    for i in 0..4 {
+        let center = (i as f64 + 0.5) * ratio - 0.5;
+        let (mut sum, mut total) = (0.0, 0.0);
+        for j in ((center - ratio).ceil() as i32)..=((center + ratio).floor() as i32) {
+            let weight = (1.0 - (j as f64 - center).abs() / ratio).max(0.0);
+            sum += weight * row.get_pixel(j.clamp(0, 9) as u32, 0)[0];
+            total += weight;
+        }
+        assert!((output.get_pixel(i, 0)[0] - sum / total).abs() < 1e-9);
+    }
+
+    // Unlike point-sampled bilinear scaling, the tent covers every input pixel
+    let scaled = transform::scale(&img, 0.25, 0.25, Scale::Triangle).unwrap();
+    assert_eq!(transform::resize(&img, 3, 3, Scale::Triangle).unwrap(), scaled);
+    assert_ne!(transform::scale(&img, 0.25, 0.25, Scale::Bilinear).unwrap(), scaled);
+}
+
+#[test]
+fn resize_catmull_rom_test() {
+    let img: Image<f64> = noise_image(5, 4, 3, 9).into();
+
+    // Every third output pixel along each axis lands exactly on an input sample
+    let catmull_rom = transform::resize(&img, 15, 12, Scale::CatmullRom).unwrap();
+    let bicubic = transform::resize(&img, 15, 12, Scale::Bicubic).unwrap();
+    let mut bicubic_exact = true;
+    for y in 0..4 {
+        for x in 0..5 {
+            let expected = img.get_pixel(x, y);
+            for (c, val) in catmull_rom.get_pixel(3 * x + 1, 3 * y + 1).iter().enumerate() {
+                assert!((val - expected[c]).abs() < 1e-9);
+            }
+            bicubic_exact &= bicubic.get_pixel(3 * x + 1, 3 * y + 1) == expected;
+        }
+    }
+
+    assert!(!bicubic_exact);
+}
+
+#[test]
+fn resize_linear_test() {
+    let img = checkerboard(8, 8);
+
+    let naive: Image<u8> = transform::resize(&img.clone().into(), 4, 4, Scale::Bilinear).unwrap().into();
+    let linear = transform::resize_linear(&img, 4, 4, Scale::Bilinear).unwrap();
+
+    // Border pixels are biased by edge clamping of the widened kernel
+    for y in 1..3 {
+        for x in 1..3 {
+            assert_eq!(128, naive.get_pixel(x, y)[0]);
+            assert_eq!(188, linear.get_pixel(x, y)[0]);
+        }
+    }
+}
+
+#[test]
+fn resize_premultiplied_test() {
+    let img = Image::from_vec(2, 1, 4, true, vec![255.0, 0.0, 0.0, 255.0, 0.0, 0.0, 0.0, 0.0]);
+    let output = transform::resize_premultiplied(&img, 4, 1, Scale::Bilinear).unwrap();
+
+    for x in 0..4 {
+        let pixel = output.get_pixel(x, 0);
+        if pixel[3] > 0.0 {
+            assert!((pixel[0] - 255.0).abs() < 1e-9);
+        }
+    }
+    assert!(output.get_pixel(1, 0)[3] < 255.0 && output.get_pixel(1, 0)[3] > 0.0);
+
+    // The default resize filters alpha like any other channel
+    let naive = transform::resize(&img, 4, 1, Scale::Bilinear).unwrap();
+    assert!(naive.get_pixel(1, 0)[0] < 250.0);
+}
+
+#[test]
+fn trim_test() {
+    // A 3x2 red center inside a white border that is 2 pixels wide on the left and 1 elsewhere
+    let mut img = Image::from_vec(6, 4, 3, false, vec![255u8; 72]);
+    for y in 1..3 {
+        for x in 2..5 {
+            img.set_pixel(x, y, &[250, 0, 0]);
+        }
+    }
+
+    let output = transform::trim(&img, 10).unwrap();
+    assert_eq!((3, 2), output.info().wh());
+    assert!(output.data().chunks(3).all(|p| p == [250, 0, 0]));
+
+    // The center is within tolerance of the border
+    assert_eq!(img, transform::trim(&img, 255).unwrap());
+
+    // Transparent rows and columns are trimmed regardless of color
+    let mut rgba = Image::from_vec(3, 3, 4, true, vec![0u8; 36]);
+    rgba.set_pixel(1, 0, &[9, 9, 9, 0]);
+    rgba.set_pixel(1, 1, &[20, 40, 60, 255]);
+    assert_eq!(&[20, 40, 60, 255], transform::trim(&rgba, 0).unwrap().data());
+}
+
+#[test]
+fn overlay_mut_test() {
+    let mut base = Image::from_vec(4, 4, 3, false, vec![0u8; 48]);
+    let top = Image::from_vec(3, 3, 3, false, vec![200u8; 27]);
+
+    // Only the lower right 2x2 corner of `top` lands on `base`
+    transform::overlay_mut(&mut base, &top, -1, -1).unwrap();
+    for y in 0..4 {
+        for x in 0..4 {
+            let expected = if x < 2 && y < 2 { [200; 3] } else { [0; 3] };
+            assert_eq!(&expected, base.get_pixel(x, y));
+        }
+    }
+
+    // Entirely off the canvas
+    let copy = base.clone();
+    transform::overlay_mut(&mut base, &top, 4, -10).unwrap();
+    assert_eq!(copy, base);
+
+    // Alpha blending
+    let half = Image::from_vec(1, 1, 4, true, vec![100u8, 100, 100, 128]);
+    transform::overlay_mut(&mut base, &half, 3, 3).unwrap();
+    assert_eq!(&[50, 50, 50], base.get_pixel(3, 3));
+
+    let gray = Image::from_vec(1, 1, 1, false, vec![0u8]);
+    assert!(transform::overlay_mut(&mut base, &gray, 0, 0).is_err());
+}
+
+#[test]
+fn tile_test() {
+    let img = Image::from_vec(2, 2, 1, false, vec![1u8, 2, 3, 4]);
+
+    let output = transform::tile(&img, 4, 4).unwrap();
+    assert_eq!((4, 4), output.info().wh());
+    for (x, y) in [(0, 0), (2, 0), (0, 2), (2, 2)].iter() {
+        assert_eq!(img.data(), &output.subimage(*x, *y, 2, 2).unwrap().to_vec()[..]);
+    }
+
+    assert_eq!(&[1, 2, 1, 3, 4, 3], transform::tile(&img, 3, 2).unwrap().data());
+    assert!(transform::tile(&Image::<u8>::from_vec(0, 0, 1, false, vec![]), 4, 4).is_err());
+}
+
+#[test]
+fn montage_test() {
+    let images: Vec<Image<u8>> = (1..=4)
+        .map(|i| Image::from_vec(2, 2, 1, false, vec![i * 10; 4]))
+        .collect();
+
+    let output = transform::montage(&images, 2, 1, &[255]).unwrap();
+    assert_eq!((5, 5), output.info().wh());
+
+    for (i, (x, y)) in [(0, 0), (3, 0), (0, 3), (3, 3)].iter().enumerate() {
+        assert_eq!(images[i].data(), &output.subimage(*x, *y, 2, 2).unwrap().to_vec()[..]);
+    }
+    for i in 0..5 {
+        assert_eq!(&[255], output.get_pixel(2, i));
+        assert_eq!(&[255], output.get_pixel(i, 2));
+    }
+
+    // Cells are sized to the largest image
+    let mixed = vec![Image::from_vec(1, 1, 1, false, vec![7u8]), images[0].clone(), images[1].clone()];
+    let output = transform::montage(&mixed, 2, 0, &[0]).unwrap();
+    assert_eq!((4, 4), output.info().wh());
+    assert_eq!(&[7, 0, 10, 10, 0, 0, 10, 10, 20, 20, 0, 0, 20, 20, 0, 0], output.data());
+
+    assert!(transform::montage(&images, 2, 1, &[255, 255, 255]).is_err());
+    assert!(transform::montage(&images, 0, 1, &[255]).is_err());
+    assert!(transform::montage(&[], 2, 1, &[255]).is_err());
+}
+
+#[test]
+fn scale_integer_test() {
+    let img = Image::from_vec(2, 2, 3, false, (0..12).collect::<Vec<u8>>());
+
+    let output = transform::scale_integer(&img, 3).unwrap();
+    assert_eq!((6, 6), output.info().wh());
+    for y in 0..6 {
+        for x in 0..6 {
+            assert_eq!(img.get_pixel(x / 3, y / 3), output.get_pixel(x, y));
+        }
+    }
+
+    assert_eq!(img, transform::scale_integer(&img, 1).unwrap());
+    assert!(transform::scale_integer(&img, 0).is_err());
+}
+
+#[test]
+fn scale2x_test() {
+    let img = Image::from_vec(3, 3, 1, false, vec![255u8, 0, 0,
+                                                   255, 255, 0,
+                                                   255, 255, 255]);
+
+    let output = transform::scale2x(&img).unwrap();
+    assert_eq!(&[255, 255, 0, 0, 0, 0,
+                 255, 255, 255, 0, 0, 0,
+                 255, 255, 255, 0, 0, 0,
+                 255, 255, 255, 255, 255, 0,
+                 255, 255, 255, 255, 255, 255,
+                 255, 255, 255, 255, 255, 255], output.data());
+
+    // Nearest neighbor leaves the diagonal as 2x2 stair steps
+    let nearest = transform::scale_integer(&img, 2).unwrap();
+    assert_ne!(nearest, output);
+    assert_eq!(&[0], nearest.get_pixel(2, 1));
+    assert_eq!(&[255], output.get_pixel(2, 1));
+
+    // Scale2x leaves flat regions unchanged
+    let flat = Image::from_vec(2, 2, 3, false, vec![7u8; 12]);
+    assert_eq!(transform::scale_integer(&flat, 2).unwrap(), transform::scale2x(&flat).unwrap());
+}
+
+#[test]
+fn letterbox_test() {
+    let img = checkerboard(100, 50);
+    let fill = vec![7; img.info().channels as usize];
+
+    let output = transform::letterbox(&img, 64, 64, &fill).unwrap();
+    assert_eq!((64, 64), output.info().wh());
+
+    let scaled: Image<u8> = transform::resize(&img.clone().into(), 64, 32, Scale::Bilinear).unwrap().into();
+    for y in 0..64 {
+        for x in 0..64 {
+            if (16..48).contains(&y) {
+                assert_eq!(scaled.get_pixel(x, y - 16), output.get_pixel(x, y));
+            } else {
+                assert_eq!(&fill[..], output.get_pixel(x, y));
+            }
+        }
+    }
+
+    assert!(transform::letterbox(&img, 64, 64, &[7, 7, 7, 7, 7]).is_err());
+    assert!(transform::letterbox(&img, 0, 64, &fill).is_err());
+}
+
+#[test]
+fn resize_by_test() {
+    let img = checkerboard(10, 8);
+
+    let half = transform::resize_by(&img, 0.5, Scale::NearestNeighbor).unwrap();
+    assert_eq!((5, 4), half.info().wh());
+    assert_eq!(img.get_pixel(1, 1), half.get_pixel(0, 0));
+
+    // Dimensions are rounded
+    assert_eq!((5, 4), transform::resize_by(&checkerboard(10, 7), 0.5, Scale::Bilinear).unwrap().info().wh());
+
+    assert_eq!(img, transform::resize_by(&img, 1.0, Scale::Bicubic).unwrap());
+    assert_eq!((1, 1), transform::resize_by(&img, 0.01, Scale::Bilinear).unwrap().info().wh());
+
+    assert!(transform::resize_by(&img, 0.0, Scale::Bilinear).is_err());
+    assert!(transform::resize_by(&img, -2.0, Scale::Bilinear).is_err());
+    assert!(transform::resize_by(&img, f64::NAN, Scale::Bilinear).is_err());
+    assert!(transform::resize_by(&img, f64::INFINITY, Scale::Bilinear).is_err());
+}
+
+#[test]
+fn resize_fraction_test() {
+    let img: Image<u8> = Image::blank(ImageInfo::new(1200, 800, 1, false));
+    assert_eq!((800, 533), transform::resize_fraction(&img, 2, 3, Scale::Bilinear).unwrap().info().wh());
+    assert_eq!((800, 533), transform::resize_fraction(&img, 4, 6, Scale::Bilinear).unwrap().info().wh());
+
+    let img = checkerboard(6, 3);
+    assert_eq!((4, 2), transform::resize_fraction(&img, 2, 3, Scale::NearestNeighbor).unwrap().info().wh());
+    assert_eq!((1, 1), transform::resize_fraction(&img, 1, 100, Scale::Bilinear).unwrap().info().wh());
+    assert_eq!(img, transform::resize_fraction(&img, 5, 5, Scale::Bicubic).unwrap());
+
+    assert!(transform::resize_fraction(&img, 2, 0, Scale::Bilinear).is_err());
+    assert!(transform::resize_fraction(&img, 0, 3, Scale::Bilinear).is_err());
+}
+
+#[test]
+fn transpose_test() {
+    let img = Image::from_vec(3, 2, 2, true, (0..12).collect::<Vec<u8>>());
+
+    let output = transform::transpose(&img);
+    assert_eq!((2, 3), output.info().wh());
+    assert_eq!(img.get_pixel(2, 1), output.get_pixel(1, 2));
+    assert_eq!(&[4, 5], output.get_pixel(0, 2));
+    assert_eq!(img, transform::transpose(&output));
+}
+
+#[test]
+fn apply_orientation_test() {
+    let img = Image::from_vec(3, 2, 1, false, vec![1, 2, 3,
+                                                   4, 5, 6]);
+
+    // Rotating 90 degrees clockwise
+    let output = transform::apply_orientation(&img, Orientation::Rotate90);
+    assert_eq!(Image::from_vec(2, 3, 1, false, vec![4, 1, 5, 2, 6, 3]), output);
+
+    let orientations = [
+        (Orientation::Normal, Orientation::Normal),
+        (Orientation::FlipHorizontal, Orientation::FlipHorizontal),
+        (Orientation::Rotate180, Orientation::Rotate180),
+        (Orientation::FlipVertical, Orientation::FlipVertical),
+        (Orientation::Transpose, Orientation::Transpose),
+        (Orientation::Rotate90, Orientation::Rotate270),
+        (Orientation::Transverse, Orientation::Transverse),
+        (Orientation::Rotate270, Orientation::Rotate90),
+    ];
+
+    let img = noise_image(5, 3, 4, 3);
+    for (orientation, inverse) in orientations.iter() {
+        let output = transform::apply_orientation(&img, *orientation);
+        assert_eq!(img, transform::apply_orientation(&output, *inverse));
+    }
+}
+
+#[test]
+fn suggest_orientation_test() {
+    // Horizontal bands, like lines of text
+    let mut data = Vec::new();
+    for y in 0..20 {
+        let value = if (y / 3) % 2 == 0 { 30 } else { 220 };
+        data.extend(std::iter::repeat(value).take(30 * 3));
+    }
+    let img = Image::from_vec(30, 20, 3, false, data);
+    assert_eq!(Orientation::Normal, transform::suggest_orientation(&img));
+
+    // The same image lying on its side
+    let rotated = transform::apply_orientation(&img, Orientation::Rotate270);
+    let suggested = transform::suggest_orientation(&rotated);
+    assert_eq!(Orientation::Rotate90, suggested);
+
+    let corrected = transform::apply_orientation(&rotated, suggested);
+    assert_eq!(img.info(), corrected.info());
+    assert_eq!(Orientation::Normal, transform::suggest_orientation(&corrected));
+
+    // Noise has no dominant direction
+    assert_eq!(Orientation::Normal, transform::suggest_orientation(&noise_image(30, 20, 3, 7)));
+
+    // Too small to have any interior pixels
+    let empty: Image<u8> = Image::blank(ImageInfo::new(0, 0, 3, false));
+    assert_eq!(Orientation::Normal, transform::suggest_orientation(&empty));
+}
+
+#[test]
+fn resize_canvas_test() {
+    let img = noise_image(4, 3, 3, 5);
+    let fill = [9, 8, 7];
+
+    // Enlarging with a centered anchor adds equal borders on each side
+    let output = transform::resize_canvas(&img, 8, 7, Anchor::Center, &fill).unwrap();
+    assert_eq!((8, 7), output.info().wh());
+    for y in 0..7 {
+        for x in 0..8 {
+            if (2..6).contains(&x) && (2..5).contains(&y) {
+                assert_eq!(img.get_pixel(x - 2, y - 2), output.get_pixel(x, y));
+            } else {
+                assert_eq!(&fill[..], output.get_pixel(x, y));
+            }
+        }
+    }
+
+    // Shrinking with a centered anchor crops equally from each side
+    let output = transform::resize_canvas(&img, 2, 1, Anchor::Center, &fill).unwrap();
+    assert_eq!(transform::crop(&img, 1, 1, 2, 1).unwrap(), output);
+
+    let output = transform::resize_canvas(&img, 5, 5, Anchor::BottomRight, &fill).unwrap();
+    assert_eq!(img.get_pixel(0, 0), output.get_pixel(1, 2));
+    assert_eq!(img.get_pixel(3, 2), output.get_pixel(4, 4));
+    assert_eq!(&fill[..], output.get_pixel(0, 4));
+
+    assert_eq!(img, transform::resize_canvas(&img, 4, 3, Anchor::TopRight, &fill).unwrap());
+    assert!(transform::resize_canvas(&img, 8, 7, Anchor::Center, &[9, 8]).is_err());
+    assert!(transform::resize_canvas(&img, 0, 7, Anchor::Center, &fill).is_err());
+}
+
+#[test]
+fn downsample_test() {
+    let img = checkerboard(16, 15);
+
+    let output = transform::downsample(&img, 2).unwrap();
+    assert_eq!((8, 8), output.info().wh());
+    // Clamped edges bias the corners slightly towards the corner pixel
+    assert!(output.data().iter().all(|val| (*val as i32 - 128).abs() <= 40));
+    for y in 1..7 {
+        for x in 1..7 {
+            assert!((output.get_pixel(x, y)[0] as i32 - 128).abs() <= 4);
+        }
+    }
+
+    // Naively keeping every other pixel only keeps the black squares
+    let naive: Vec<u8> = (0..8).flat_map(|y| (0..8).map(move |x| (x, y)))
+        .map(|(x, y)| img.get_pixel(2 * x, 2 * y)[0])
+        .collect();
+    assert!(naive.iter().all(|val| *val == 0));
+
+    assert_eq!(img, transform::downsample(&img, 1).unwrap());
+    assert_eq!((4, 4), transform::downsample(&img, 4).unwrap().info().wh());
+    assert!(transform::downsample(&img, 0).is_err());
+}
+
+#[test]
+fn polar_test() {
+    // Rings of width 8 around the center
+    let rings = Image::from_vec(41, 41, 1, false, (0..(41 * 41)).map(|i| {
+        let (x, y) = ((i % 41) as f64 - 20.0, (i / 41) as f64 - 20.0);
+        if (x.hypot(y) / 8.0) as u32 % 2 == 0 { 255 } else { 0 }
+    }).collect());
+
+    let polar = transform::to_polar(&rings, (20, 20)).unwrap();
+    assert_eq!(1, polar.info().channels);
+    assert!(polar.info().height > 20);
+
+    // Rows away from the ring boundaries are constant bands
+    for r in [3, 4, 5, 11, 12, 13, 19].iter() {
+        let expected = if (*r / 8) % 2 == 0 { 255 } else { 0 };
+        for x in 0..polar.info().width {
+            assert_eq!(expected, polar.get_pixel(x, *r)[0]);
+        }
+    }
+
+    // Converting back approximately reconstructs a smooth image
+    let img = Image::linear_gradient(32, 24, &[0, 50, 200], &[255, 150, 0], true).unwrap();
+    let polar = transform::to_polar(&img, (16, 12)).unwrap();
+    let output = transform::from_polar(&polar, (16, 12), 32, 24).unwrap();
+    assert_eq!(img.info(), output.info());
+
+    // Edge pixels are skipped since they are interpolated with the black samples outside `img`
+    let mut max_error = 0;
+    for y in 1..23 {
+        for x in 1..31 {
+            for (a, b) in img.get_pixel(x, y).iter().zip(output.get_pixel(x, y).iter()) {
+                max_error = max_error.max((*a as i32 - *b as i32).abs());
+            }
+        }
+    }
+    assert!(max_error <= 2, "max error {}", max_error);
+
+    assert!(transform::to_polar(&img, (32, 0)).is_err());
+    assert!(transform::from_polar(&polar, (0, 24), 32, 24).is_err());
+}
+
+#[test]
+fn warp_perspective_test() {
+    let img = noise_image(12, 9, 3, 6);
+    let identity = [1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0];
+    assert_eq!(img, transform::warp_perspective(&img, &identity, 12, 9, Scale::Bilinear).unwrap());
+    assert_eq!(img, transform::warp_perspective(&img, &identity, 12, 9, Scale::NearestNeighbor).unwrap());
+
+    // The homography maps the trapezoid with these corners onto the square [20, 60] x [20, 60]
+    let corners = [(50.0 / 3.0, 50.0 / 3.0), (50.0, 50.0 / 3.0), (37.5, 37.5), (12.5, 37.5)];
+    let homography = [1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, -0.01, 1.0];
+
+    let trapezoid = Image::from_vec(64, 64, 1, false, (0..(64 * 64)).map(|i| {
+        let (x, y) = ((i % 64) as f64, (i / 64) as f64);
+        let inside = (0..4).all(|k| {
+            let ((x_0, y_0), (x_1, y_1)) = (corners[k], corners[(k + 1) % 4]);
+            (x_1 - x_0) * (y - y_0) - (y_1 - y_0) * (x - x_0) >= 0.0
+        });
+        if inside { 255 } else { 0 }
+    }).collect());
+
+    let output = transform::warp_perspective(&trapezoid, &homography, 80, 80, Scale::Bilinear).unwrap();
+    for y in 0..80 {
+        for x in 0..80 {
+            if (22..=58).contains(&x) && (22..=58).contains(&y) {
+                assert_eq!(255, output.get_pixel(x, y)[0]);
+            } else if !(18..=62).contains(&x) || !(18..=62).contains(&y) {
+                assert_eq!(0, output.get_pixel(x, y)[0]);
+            }
+        }
+    }
+
+    let singular = [1.0, 2.0, 3.0, 2.0, 4.0, 6.0, 0.0, 0.0, 1.0];
+    assert!(transform::warp_perspective(&img, &singular, 12, 9, Scale::Bilinear).is_err());
+}
+
+#[test]
+fn homography_from_points_test() {
+    let square = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)];
+    let identity = [1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0];
+    let output = transform::homography_from_points(square, square).unwrap();
+    for (a, b) in identity.iter().zip(output.iter()) {
+        assert!((a - b).abs() < 1e-12);
+    }
+
+    let expected = [1.0, 0.5, 2.0, 0.2, 1.0, -1.0, 0.001, 0.002, 1.0];
+    let src = [(10.0, 5.0), (90.0, 12.0), (80.0, 70.0), (4.0, 60.0)];
+    let mut dst = [(0.0, 0.0); 4];
+    for ((x, y), p) in src.iter().zip(dst.iter_mut()) {
+        let w = expected[6] * x + expected[7] * y + expected[8];
+        *p = ((expected[0] * x + expected[1] * y + expected[2]) / w,
+              (expected[3] * x + expected[4] * y + expected[5]) / w);
+    }
+
+    let output = transform::homography_from_points(src, dst).unwrap();
+    for (a, b) in expected.iter().zip(output.iter()) {
+        assert!((a - b).abs() < 1e-9);
+    }
+
+    let collinear = [(0.0, 0.0), (1.0, 1.0), (2.0, 2.0), (0.0, 1.0)];
+    assert!(transform::homography_from_points(collinear, square).is_err());
+}
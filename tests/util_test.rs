@@ -25,4 +25,21 @@ fn rectangular_intensity_sum_test() {
     assert_eq!(vec![154.0], util::rectangular_intensity_sum(&input, 1, 1, 4, 2));
     assert_eq!(vec![198.0], util::rectangular_intensity_sum(&input, 1, 0, 4, 2));
     assert_eq!(vec![254.0], util::rectangular_intensity_sum(&input, 0, 0, 4, 2));
-}
\ No newline at end of file
+}
+
+#[test]
+fn diff_highlight_test() {
+    let a: Image<u8> = Image::from_vec(2, 2, 4, true, vec![10, 20, 30, 255, 40, 50, 60, 128,
+                                                           70, 80, 90, 255, 100, 110, 120, 0]);
+    let output = util::diff_highlight(&a, &a, 0, [255, 0, 255]).unwrap();
+    assert_eq!(a, output);
+
+    let mut b = a.clone();
+    b.set_pixel(1, 0, &[40, 50, 70, 128]);
+    b.set_pixel(0, 1, &[72, 80, 90, 255]);
+    let output = util::diff_highlight(&a, &b, 5, [255, 0, 255]).unwrap();
+    assert_eq!(&[10, 20, 30, 255, 255, 0, 255, 128, 70, 80, 90, 255, 100, 110, 120, 0], output.data());
+
+    let c: Image<u8> = Image::from_vec(1, 2, 4, true, vec![0; 8]);
+    assert!(util::diff_highlight(&a, &c, 5, [255, 0, 255]).is_err());
+}